@@ -1,5 +1,5 @@
 use rust_tracer::{
-    canvas::Canvas,
+    canvas::{text_size, Canvas},
     transformations::Transformation,
     tuples::{Point, Scalar, Tuple},
 };
@@ -16,11 +16,23 @@ fn main() {
     let mut c = Canvas::new(size, size);
     let white = Tuple::color(1., 1., 1.);
     let half = (size as Scalar) / 2.;
-    for hour in &hours {
-        c.write_pixel(
-            (hour.x * radius + half) as usize,
-            (hour.z * radius + half) as usize,
+    for (i, hour) in hours.iter().enumerate() {
+        let center = (hour.x * radius + half, hour.z * radius + half);
+        c.fill_circle(center, 6., white);
+
+        let label = (if i == 0 { 12 } else { i }).to_string();
+        let scale = 2;
+        let (width, height) = text_size(&label, scale);
+        let outside = (
+            hour.x * (radius + 30.) + half,
+            hour.z * (radius + 30.) + half,
+        );
+        c.draw_text(
+            (outside.0 - width as Scalar / 2.) as usize,
+            (outside.1 - height as Scalar / 2.) as usize,
+            &label,
             white,
+            scale,
         );
     }
 
@@ -4,7 +4,7 @@ use rust_tracer::{
 };
 use std::{f64::consts::*, path::Path};
 
-fn main() {
+pub fn world() -> World {
     let mut floor = Shape::plane();
     *floor.material_mut() = Material::default();
     floor.material_mut().set_color(Tuple::color(1., 0.9, 0.9));
@@ -37,15 +37,20 @@ fn main() {
 
     let light_source = PointLight::new(Tuple::point(-10., 10., -10.), Tuple::color(1., 1., 1.));
 
-    let world = World::with_objects_and_light(vec![floor, middle, right, left], light_source);
+    World::with_objects_and_light(vec![floor, middle, right, left], light_source)
+}
 
-    let mut camera = Camera::new(800, 600, FRAC_PI_3);
+pub fn camera(hsize: usize, vsize: usize) -> Camera {
+    let mut camera = Camera::new(hsize, vsize, FRAC_PI_3);
     camera.set_transform(Transformation::view(
         &Tuple::point(0., 1.5, -5.),
         &Tuple::point(0., 1., 0.),
         &Tuple::vector(0., 1., 0.),
     ));
+    camera
+}
 
-    let canvas = camera.render(&world);
+fn main() {
+    let canvas = camera(800, 600).render(&world());
     canvas.save_to_file(Path::new("plane.ppm")).unwrap();
 }
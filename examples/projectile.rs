@@ -34,9 +34,11 @@ fn main() {
     };
     let mut c = Canvas::new(900, 550);
     let red = Tuple::color(1.0, 0.0, 0.0);
+    let to_canvas = |position: Point| (position.x, 550.0 - position.y);
     loop {
-        c.write_pixel(p.position.x as usize, 550 - 1 - p.position.y as usize, red);
-        p = e.tick(&p);
+        let next = e.tick(&p);
+        c.draw_line(to_canvas(p.position), to_canvas(next.position), red);
+        p = next;
         if p.position.y < 0.0 {
             break;
         }
@@ -4,21 +4,26 @@ use rust_tracer::{
 };
 use std::{f64::consts::*, path::Path};
 
-fn main() {
-    let mut camera = Camera::new(400, 200, 0.524);
+pub fn camera(hsize: usize, vsize: usize) -> Camera {
+    let mut camera = Camera::new(hsize, vsize, 0.524);
     camera.set_transform(Transformation::view(
         &Tuple::point(40., 0., -70.),
         &Tuple::point(0., 0., -5.),
         &Tuple::vector(0., 1., 0.),
     ));
+    camera
+}
 
+pub fn world() -> World {
     let light = PointLight::new(Tuple::point(0., 0., -100.), Tuple::color(1., 1., 1.));
 
-    let mut sphere_material = Material::default();
-    sphere_material.ambient = 0.2;
-    sphere_material.diffuse = 0.8;
-    sphere_material.specular = 0.3;
-    sphere_material.shininess = 200.;
+    let sphere_material = Material {
+        ambient: 0.2,
+        diffuse: 0.8,
+        specular: 0.3,
+        shininess: 200.,
+        ..Default::default()
+    };
 
     let mut wrist_material = sphere_material.clone();
     wrist_material.set_color(Tuple::color(0.1, 1., 1.));
@@ -97,11 +102,13 @@ fn main() {
             * Transformation::scaling(2.5, 0.6, 0.6),
     );
 
-    let world = World::with_objects_and_light(
+    World::with_objects_and_light(
         vec![backdrop, wrist, palm, thumb, index, middle, ring, pinky],
         light,
-    );
+    )
+}
 
-    let canvas = camera.render(&world);
+fn main() {
+    let canvas = camera(400, 200).render(&world());
     canvas.save_to_file(Path::new("shadows.ppm")).unwrap();
 }
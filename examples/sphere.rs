@@ -5,7 +5,7 @@ use rust_tracer::{
     materials::Material,
     rays::Ray,
     shapes::Shape,
-    tuples::{Scalar, Tuple},
+    tuples::{Color, Scalar, Tuple},
 };
 use std::{fs::File, io::Write, path::Path};
 
@@ -38,10 +38,14 @@ fn main() {
                 let point = r.position(hit.t);
                 let normal = hit.object.normal_at(&point);
                 let eye = -r.direction;
-                let color = hit
-                    .object
-                    .material()
-                    .lighting(&shape, &light, &point, &eye, &normal, false);
+                let color = hit.object.material().lighting(
+                    &shape,
+                    &light,
+                    &point,
+                    &eye,
+                    &normal,
+                    &Color::WHITE,
+                );
                 canvas.write_pixel(x, y, color);
             }
         }
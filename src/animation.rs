@@ -0,0 +1,536 @@
+use crate::{
+    camera::Camera,
+    transformations::Transformation,
+    tuples::{Color, Point, Scalar, Tuple},
+    world::World,
+};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    path::Path,
+};
+
+/// How a value moves from one keyframe to the next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    Smoothstep,
+    /// A cubic Bézier timing curve from (0, 0) to (1, 1) through the control points
+    /// (x1, y1) and (x2, y2), as in CSS. The x coordinates must lie in [0, 1].
+    Bezier(Scalar, Scalar, Scalar, Scalar),
+}
+
+impl Easing {
+    pub fn apply(&self, t: Scalar) -> Scalar {
+        let t = t.clamp(0., 1.);
+        match *self {
+            Easing::Linear => t,
+            Easing::Smoothstep => t * t * (3. - 2. * t),
+            Easing::Bezier(x1, y1, x2, y2) => {
+                let (mut low, mut high) = (0., 1.);
+                for _ in 0..BEZIER_ITERATIONS {
+                    let s = (low + high) / 2.;
+                    if bezier(x1, x2, s) < t {
+                        low = s;
+                    } else {
+                        high = s;
+                    }
+                }
+                bezier(y1, y2, (low + high) / 2.)
+            }
+        }
+    }
+}
+
+const BEZIER_ITERATIONS: usize = 40;
+
+/// One coordinate of a cubic Bézier curve starting at 0 and ending at 1.
+fn bezier(p1: Scalar, p2: Scalar, s: Scalar) -> Scalar {
+    let r = 1. - s;
+    3. * r * r * s * p1 + 3. * r * s * s * p2 + s * s * s
+}
+
+/// Values that can be blended between keyframes. Transformations are blended through
+/// their translation, rotation and stretch, so a rotating camera or shape keeps its
+/// size between keyframes.
+pub trait Interpolate: Copy {
+    fn interpolate(&self, other: &Self, t: Scalar) -> Self;
+}
+
+impl Interpolate for Scalar {
+    fn interpolate(&self, other: &Self, t: Scalar) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Tuple {
+    fn interpolate(&self, other: &Self, t: Scalar) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Interpolate for Transformation {
+    fn interpolate(&self, other: &Self, t: Scalar) -> Self {
+        Transformation::interpolate(self, other, t)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: Scalar,
+    pub value: T,
+    /// Easing on the way to the next keyframe.
+    pub easing: Easing,
+}
+
+/// Keyframes of one value ordered by time. Before the first and after the last
+/// keyframe the value stays put.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate> Track<T> {
+    pub fn new() -> Track<T> {
+        Track { keyframes: vec![] }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /// Adds a keyframe, replacing one at the same time.
+    pub fn add(&mut self, time: Scalar, value: T, easing: Easing) {
+        let keyframe = Keyframe {
+            time,
+            value,
+            easing,
+        };
+        let index = self.keyframes.partition_point(|k| k.time < time);
+        match self.keyframes.get(index) {
+            Some(k) if k.time == time => self.keyframes[index] = keyframe,
+            _ => self.keyframes.insert(index, keyframe),
+        }
+    }
+
+    pub fn with(mut self, time: Scalar, value: T, easing: Easing) -> Track<T> {
+        self.add(time, value, easing);
+        self
+    }
+
+    pub fn value_at(&self, time: Scalar) -> Option<T> {
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return self.keyframes.first().map(|k| k.value);
+        }
+        let from = &self.keyframes[next - 1];
+        let Some(to) = self.keyframes.get(next) else {
+            return Some(from.value);
+        };
+        let t = (time - from.time) / (to.time - from.time);
+        Some(from.value.interpolate(&to.value, from.easing.apply(t)))
+    }
+}
+
+impl<T: Interpolate> Default for Track<T> {
+    fn default() -> Self {
+        Track::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaterialParameter {
+    Ambient,
+    Diffuse,
+    Specular,
+    Shininess,
+    Metallic,
+    Roughness,
+    Transparency,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationError {
+    MissingShape(usize),
+    MissingLight(usize),
+}
+
+impl Display for AnimationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimationError::MissingShape(index) => write!(f, "No shape with index {index}"),
+            AnimationError::MissingLight(index) => write!(f, "No light with index {index}"),
+        }
+    }
+}
+
+impl Error for AnimationError {}
+
+/// Tracks for parts of a scene, with shapes and lights referred to by their index in
+/// the world.
+#[derive(Debug, Clone, Default)]
+pub struct Animation {
+    camera_transform: Option<Track<Transformation>>,
+    shape_transforms: Vec<(usize, Track<Transformation>)>,
+    light_positions: Vec<(usize, Track<Point>)>,
+    material_parameters: Vec<(usize, MaterialParameter, Track<Scalar>)>,
+    emissive_colors: Vec<(usize, Track<Color>)>,
+}
+
+impl Animation {
+    pub fn new() -> Animation {
+        Animation::default()
+    }
+
+    pub fn set_camera_transform(&mut self, track: Track<Transformation>) {
+        self.camera_transform = Some(track);
+    }
+
+    /// Posing the shape sets its transform, which stops any motion set on it.
+    pub fn add_shape_transform(&mut self, shape: usize, track: Track<Transformation>) {
+        self.shape_transforms.push((shape, track));
+    }
+
+    pub fn add_light_position(&mut self, light: usize, track: Track<Point>) {
+        self.light_positions.push((light, track));
+    }
+
+    pub fn add_material_parameter(
+        &mut self,
+        shape: usize,
+        parameter: MaterialParameter,
+        track: Track<Scalar>,
+    ) {
+        self.material_parameters.push((shape, parameter, track));
+    }
+
+    pub fn add_emissive_color(&mut self, shape: usize, track: Track<Color>) {
+        self.emissive_colors.push((shape, track));
+    }
+
+    /// Checks that every shape and light the tracks refer to exists in `world`.
+    pub fn validate(&self, world: &World) -> Result<(), AnimationError> {
+        let mut shapes = self
+            .shape_transforms
+            .iter()
+            .map(|(index, _)| index)
+            .chain(self.material_parameters.iter().map(|(index, _, _)| index))
+            .chain(self.emissive_colors.iter().map(|(index, _)| index));
+        if let Some(index) = shapes.find(|index| **index >= world.objects().len()) {
+            return Err(AnimationError::MissingShape(*index));
+        }
+        if let Some((index, _)) = self
+            .light_positions
+            .iter()
+            .find(|(index, _)| *index >= world.lights().len())
+        {
+            return Err(AnimationError::MissingLight(*index));
+        }
+        Ok(())
+    }
+
+    /// Poses the world and the camera as they are at `time`. Fails without changing
+    /// anything when a track refers to a shape or light that is not in `world`.
+    pub fn apply(
+        &self,
+        time: Scalar,
+        world: &mut World,
+        camera: &mut Camera,
+    ) -> Result<(), AnimationError> {
+        self.validate(world)?;
+        if let Some(transform) = self
+            .camera_transform
+            .as_ref()
+            .and_then(|track| track.value_at(time))
+        {
+            camera.set_transform(transform);
+        }
+        for (index, track) in &self.shape_transforms {
+            if let Some(transform) = track.value_at(time) {
+                world.objects_mut()[*index].set_transform(transform);
+            }
+        }
+        for (index, track) in &self.light_positions {
+            if let Some(position) = track.value_at(time) {
+                world.lights_mut()[*index].position = position;
+            }
+        }
+        for (index, parameter, track) in &self.material_parameters {
+            let Some(value) = track.value_at(time) else {
+                continue;
+            };
+            let material = world.objects_mut()[*index].material_mut();
+            let field = match parameter {
+                MaterialParameter::Ambient => &mut material.ambient,
+                MaterialParameter::Diffuse => &mut material.diffuse,
+                MaterialParameter::Specular => &mut material.specular,
+                MaterialParameter::Shininess => &mut material.shininess,
+                MaterialParameter::Metallic => &mut material.metallic,
+                MaterialParameter::Roughness => &mut material.roughness,
+                MaterialParameter::Transparency => &mut material.transparency,
+            };
+            *field = value;
+        }
+        for (index, track) in &self.emissive_colors {
+            if let Some(color) = track.value_at(time) {
+                world.objects_mut()[*index].material_mut().emissive = color;
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders `frames` frames at `frames_per_second` into `directory` as
+    /// `frame_0001.png`, `frame_0002.png` and so on, the first one at time 0. Tracks
+    /// are validated by posing the first frame, before anything is rendered.
+    pub fn render_frames(
+        &self,
+        world: &mut World,
+        camera: &mut Camera,
+        frames: usize,
+        frames_per_second: Scalar,
+        directory: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        for frame in 0..frames {
+            self.apply(frame as Scalar / frames_per_second, world, camera)?;
+            let path = directory.join(frame_file_name(frame + 1));
+            camera.render(world).save_to_file(&path)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn frame_file_name(number: usize) -> String {
+    format!("frame_{number:04}.png")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{canvas::Canvas, lights::PointLight, shapes::Shape};
+    use approx::assert_abs_diff_eq;
+    use std::{
+        env,
+        f64::consts::{FRAC_PI_2, FRAC_PI_3},
+        fs, process,
+    };
+
+    #[test]
+    fn easing_curves_start_at_zero_and_end_at_one() {
+        for easing in [
+            Easing::Linear,
+            Easing::Smoothstep,
+            Easing::Bezier(0.25, 0.1, 0.25, 1.),
+        ] {
+            assert_abs_diff_eq!(easing.apply(0.), 0., epsilon = 1e-9);
+            assert_abs_diff_eq!(easing.apply(1.), 1., epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn smoothstep_eases_in_and_out() {
+        assert_eq!(Easing::Smoothstep.apply(0.5), 0.5);
+        assert_abs_diff_eq!(Easing::Smoothstep.apply(0.25), 0.15625);
+        assert_eq!(Easing::Smoothstep.apply(2.), 1.);
+    }
+
+    #[test]
+    fn a_bezier_with_control_points_on_the_diagonal_is_linear() {
+        let easing = Easing::Bezier(1. / 3., 1. / 3., 2. / 3., 2. / 3.);
+
+        for t in [0.1, 0.3, 0.5, 0.8] {
+            assert_abs_diff_eq!(easing.apply(t), t, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn a_bezier_can_ease_in() {
+        let easing = Easing::Bezier(0.42, 0., 1., 1.);
+
+        assert!(easing.apply(0.25) < 0.25);
+        assert!(easing.apply(0.5) < 0.5);
+    }
+
+    #[test]
+    fn keyframes_are_kept_in_order() {
+        let track = Track::new()
+            .with(2., 20., Easing::Linear)
+            .with(0., 0., Easing::Linear)
+            .with(1., 10., Easing::Linear)
+            .with(2., 30., Easing::Linear);
+
+        let times: Vec<Scalar> = track.keyframes().iter().map(|k| k.time).collect();
+        assert_eq!(times, vec![0., 1., 2.]);
+        assert_eq!(track.keyframes()[2].value, 30.);
+    }
+
+    #[test]
+    fn a_track_interpolates_between_keyframes() {
+        let track = Track::new()
+            .with(1., 10., Easing::Linear)
+            .with(3., 20., Easing::Smoothstep)
+            .with(5., 0., Easing::Linear);
+
+        assert_eq!(track.value_at(0.), Some(10.));
+        assert_eq!(track.value_at(2.), Some(15.));
+        assert_eq!(track.value_at(3.), Some(20.));
+        assert_abs_diff_eq!(track.value_at(3.5).unwrap(), 20. - 20. * 0.15625);
+        assert_eq!(track.value_at(9.), Some(0.));
+        assert_eq!(Track::<Scalar>::new().value_at(1.), None);
+    }
+
+    #[test]
+    fn interpolating_points_and_transformations() {
+        let points = Track::new()
+            .with(0., Tuple::point(0., 0., 0.), Easing::Linear)
+            .with(1., Tuple::point(2., 4., -2.), Easing::Linear);
+        let transforms = Track::new()
+            .with(0., Transformation::translation(0., 0., 0.), Easing::Linear)
+            .with(1., Transformation::translation(4., 0., 0.), Easing::Linear);
+
+        assert_eq!(points.value_at(0.5), Some(Tuple::point(1., 2., -1.)));
+        assert_eq!(
+            transforms.value_at(0.25),
+            Some(Transformation::translation(1., 0., 0.))
+        );
+    }
+
+    #[test]
+    fn a_rotating_camera_keeps_its_distance() {
+        let from = |angle: Scalar| Tuple::point(5. * angle.sin(), 0., -5. * angle.cos());
+        let view = |angle: Scalar| {
+            Transformation::view(
+                &from(angle),
+                &Tuple::point(0., 0., 0.),
+                &Tuple::vector(0., 1., 0.),
+            )
+        };
+        let track = Track::new().with(0., view(0.), Easing::Linear).with(
+            1.,
+            view(2. * FRAC_PI_3),
+            Easing::Linear,
+        );
+
+        let halfway = track.value_at(0.5).unwrap();
+
+        assert_abs_diff_eq!(halfway, view(FRAC_PI_3), epsilon = 0.00001);
+        assert_abs_diff_eq!(
+            halfway.inverse() * Tuple::point(0., 0., 0.),
+            from(FRAC_PI_3),
+            epsilon = 0.00001
+        );
+    }
+
+    #[test]
+    fn applying_an_animation_to_a_scene() {
+        let mut world = World::with_objects_and_light(
+            vec![Shape::sphere()],
+            PointLight::new(Tuple::point(0., 10., 0.), Color::WHITE),
+        );
+        let mut camera = Camera::new(5, 5, FRAC_PI_2);
+        let mut animation = Animation::new();
+        animation.set_camera_transform(
+            Track::new()
+                .with(0., Transformation::IDENTITY, Easing::Linear)
+                .with(2., Transformation::translation(0., 0., -2.), Easing::Linear),
+        );
+        animation.add_shape_transform(
+            0,
+            Track::new().with(0., Transformation::scaling(2., 2., 2.), Easing::Linear),
+        );
+        animation.add_light_position(
+            0,
+            Track::new()
+                .with(0., Tuple::point(0., 10., 0.), Easing::Linear)
+                .with(2., Tuple::point(10., 10., 0.), Easing::Linear),
+        );
+        animation.add_material_parameter(
+            0,
+            MaterialParameter::Diffuse,
+            Track::new()
+                .with(0., 1., Easing::Linear)
+                .with(2., 0., Easing::Linear),
+        );
+        animation.add_emissive_color(
+            0,
+            Track::new().with(1., Tuple::color(0., 0.5, 0.), Easing::Linear),
+        );
+
+        animation.apply(1., &mut world, &mut camera).unwrap();
+
+        assert_eq!(
+            *world.objects()[0].transform(),
+            Transformation::scaling(2., 2., 2.)
+        );
+        assert_eq!(world.lights()[0].position, Tuple::point(5., 10., 0.));
+        assert_eq!(world.objects()[0].material().diffuse, 0.5);
+        assert_eq!(
+            world.objects()[0].material().emissive,
+            Tuple::color(0., 0.5, 0.)
+        );
+        assert_eq!(
+            *camera.transform(),
+            Transformation::translation(0., 0., -1.)
+        );
+    }
+
+    #[test]
+    fn tracks_of_missing_shapes_and_lights_are_rejected() {
+        let mut world = World::default();
+        let mut camera = Camera::new(5, 5, FRAC_PI_2);
+        let diffuse = Track::new().with(0., 0.5, Easing::Linear);
+        let mut missing_shape = Animation::new();
+        missing_shape.add_material_parameter(0, MaterialParameter::Diffuse, diffuse.clone());
+        missing_shape.add_material_parameter(2, MaterialParameter::Diffuse, diffuse);
+        let mut missing_light = Animation::new();
+        missing_light.add_light_position(
+            1,
+            Track::new().with(0., Tuple::point(0., 0., 0.), Easing::Linear),
+        );
+
+        assert_eq!(
+            missing_shape.apply(0., &mut world, &mut camera),
+            Err(AnimationError::MissingShape(2))
+        );
+        assert_eq!(
+            missing_light.apply(0., &mut world, &mut camera),
+            Err(AnimationError::MissingLight(1))
+        );
+        assert_eq!(world.objects()[0].material().diffuse, 0.7);
+    }
+
+    #[test]
+    fn naming_frames() {
+        assert_eq!(frame_file_name(1), "frame_0001.png");
+        assert_eq!(frame_file_name(12345), "frame_12345.png");
+    }
+
+    #[test]
+    fn rendering_a_sequence_of_frames() {
+        let mut world = World::default();
+        let mut camera = Camera::new(4, 3, FRAC_PI_2);
+        let mut animation = Animation::new();
+        animation.add_light_position(
+            0,
+            Track::new()
+                .with(0., Tuple::point(-10., 10., -10.), Easing::Linear)
+                .with(1., Tuple::point(10., 10., -10.), Easing::Linear),
+        );
+        let directory =
+            env::temp_dir().join(format!("rust-tracer-{}-animation-frames", process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        animation
+            .render_frames(&mut world, &mut camera, 3, 2., &directory)
+            .unwrap();
+
+        for number in 1..=3 {
+            let frame = Canvas::load_from_file(&directory.join(frame_file_name(number))).unwrap();
+            assert_eq!((frame.width(), frame.height()), (4, 3));
+        }
+        assert!(!directory.join(frame_file_name(4)).exists());
+        assert_eq!(world.lights()[0].position, Tuple::point(10., 10., -10.));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,384 @@
+use crate::{
+    canvas::Canvas,
+    sampling::{uniform_sphere, Distribution1D},
+    tuples::{Color, Scalar, Tuple, Vector},
+};
+use std::{
+    f64::consts::PI,
+    fmt::{self, Debug},
+};
+
+/// A direction towards the background with its radiance and probability density per
+/// unit solid angle.
+#[derive(Debug, Clone, Copy)]
+pub struct BackgroundSample {
+    pub direction: Vector,
+    pub radiance: Color,
+    pub pdf: Scalar,
+}
+
+/// What a ray sees when it leaves the scene without hitting anything.
+pub trait Background: Debug {
+    fn color_at(&self, direction: &Vector) -> Color;
+
+    /// Picks a direction for lighting from the background, uniformly over the sphere
+    /// unless a background knows where its light comes from.
+    fn sample(&self, u: (Scalar, Scalar)) -> Option<BackgroundSample> {
+        let direction = uniform_sphere(u);
+        Some(BackgroundSample {
+            direction,
+            radiance: self.color_at(&direction),
+            pdf: 1. / (4. * PI),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Constant {
+    color: Color,
+}
+
+impl Constant {
+    pub fn new(color: Color) -> Constant {
+        Constant { color }
+    }
+}
+
+impl Background for Constant {
+    fn color_at(&self, _direction: &Vector) -> Color {
+        self.color
+    }
+}
+
+/// Blends from `bottom` straight down to `top` straight up.
+#[derive(Debug, Clone, Copy)]
+pub struct VerticalGradient {
+    bottom: Color,
+    top: Color,
+}
+
+impl VerticalGradient {
+    pub fn new(bottom: Color, top: Color) -> VerticalGradient {
+        VerticalGradient { bottom, top }
+    }
+}
+
+impl Background for VerticalGradient {
+    fn color_at(&self, direction: &Vector) -> Color {
+        let t = 0.5 * (direction.normalize().y + 1.);
+        self.bottom + (self.top - self.bottom) * t
+    }
+}
+
+/// An equirectangular image spanning all directions, with the centre of the image
+/// looking down the negative z axis and the top row straight up. Directions for
+/// lighting are sampled in proportion to the luminance of the image, taking for every
+/// texel the brightest of the texels its interpolated color draws from, so that no
+/// direction with light is left out.
+pub struct EnvironmentMap {
+    image: Canvas,
+    intensity: Scalar,
+    rows: Vec<Option<Distribution1D>>,
+    marginal: Option<Distribution1D>,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Canvas) -> EnvironmentMap {
+        Self::with_intensity(image, 1.)
+    }
+
+    /// Panics on an empty image, which has no color to give any direction.
+    pub fn with_intensity(image: Canvas, intensity: Scalar) -> EnvironmentMap {
+        let (width, height) = (image.width(), image.height());
+        assert!(
+            width > 0 && height > 0,
+            "Environment map image is empty: {width}x{height}"
+        );
+        let luminance: Vec<Scalar> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| image.pixel_at(x, y).luminance().max(0.))
+            .collect();
+        let footprint = |x: usize, y: usize| {
+            (y.saturating_sub(1)..=(y + 1).min(height - 1))
+                .flat_map(|y| [x + width - 1, x, x + 1].map(|x| luminance[y * width + x % width]))
+                .fold(0., Scalar::max)
+        };
+
+        let mut rows = Vec::with_capacity(height);
+        let mut row_weights = Vec::with_capacity(height);
+        for y in 0..height {
+            let sin_theta = (PI * (y as Scalar + 0.5) / height as Scalar).sin();
+            let weights: Vec<Scalar> = (0..width).map(|x| footprint(x, y) * sin_theta).collect();
+            row_weights.push(weights.iter().sum());
+            rows.push(Distribution1D::new(&weights));
+        }
+        EnvironmentMap {
+            image,
+            intensity,
+            rows,
+            marginal: Distribution1D::new(&row_weights),
+        }
+    }
+
+    pub fn image(&self) -> &Canvas {
+        &self.image
+    }
+
+    pub fn intensity(&self) -> Scalar {
+        self.intensity
+    }
+
+    /// Image coordinates of a direction, with `u` and `v` in the unit interval.
+    pub fn uv(direction: &Vector) -> (Scalar, Scalar) {
+        let d = direction.normalize();
+        let u = 0.5 + d.x.atan2(-d.z) / (2. * PI);
+        let v = d.y.clamp(-1., 1.).acos() / PI;
+        (u, v)
+    }
+
+    pub fn direction(u: Scalar, v: Scalar) -> Vector {
+        let phi = (u - 0.5) * 2. * PI;
+        let theta = v * PI;
+        Tuple::vector(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        )
+    }
+
+    fn texel(&self, x: isize, y: usize) -> Color {
+        let width = self.image.width() as isize;
+        self.image
+            .pixel_at(x.rem_euclid(width) as usize, y.min(self.image.height() - 1))
+    }
+}
+
+impl Debug for EnvironmentMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnvironmentMap")
+            .field("width", &self.image.width())
+            .field("height", &self.image.height())
+            .field("intensity", &self.intensity)
+            .finish()
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn color_at(&self, direction: &Vector) -> Color {
+        let (u, v) = Self::uv(direction);
+        let x = u * self.image.width() as Scalar - 0.5;
+        let y = (v * self.image.height() as Scalar - 0.5).max(0.);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as usize);
+
+        let top = self.texel(x0, y0) * (1. - fx) + self.texel(x0 + 1, y0) * fx;
+        let bottom = self.texel(x0, y0 + 1) * (1. - fx) + self.texel(x0 + 1, y0 + 1) * fx;
+        (top * (1. - fy) + bottom * fy) * self.intensity
+    }
+
+    fn sample(&self, (u, v): (Scalar, Scalar)) -> Option<BackgroundSample> {
+        let marginal = self.marginal.as_ref()?;
+        let (y, dy) = marginal.sample(v);
+        let row = self.rows[y].as_ref()?;
+        let (x, dx) = row.sample(u);
+
+        let u = (x as Scalar + dx) / self.image.width() as Scalar;
+        let v = (y as Scalar + dy) / self.image.height() as Scalar;
+        let sin_theta = (PI * v).sin();
+        if sin_theta <= 0. {
+            return None;
+        }
+        let texels = (self.image.width() * self.image.height()) as Scalar;
+        let direction = Self::direction(u, v);
+        Some(BackgroundSample {
+            direction,
+            radiance: self.color_at(&direction),
+            pdf: marginal.probability(y) * row.probability(x) * texels / (2. * PI * PI * sin_theta),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::sampling::{RandomSampler, Sampler};
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn a_constant_background_is_the_same_in_every_direction() {
+        let b = Constant::new(Tuple::color(0.1, 0.2, 0.3));
+
+        assert_eq!(
+            b.color_at(&Tuple::vector(0., 1., 0.)),
+            Tuple::color(0.1, 0.2, 0.3)
+        );
+        assert_eq!(
+            b.color_at(&Tuple::vector(1., -1., 0.5)),
+            Tuple::color(0.1, 0.2, 0.3)
+        );
+    }
+
+    #[test]
+    fn a_vertical_gradient_blends_from_bottom_to_top() {
+        let b = VerticalGradient::new(Color::BLACK, Tuple::color(0., 0.5, 1.));
+
+        assert_eq!(b.color_at(&Tuple::vector(0., -1., 0.)), Color::BLACK);
+        assert_eq!(
+            b.color_at(&Tuple::vector(0., 1., 0.)),
+            Tuple::color(0., 0.5, 1.)
+        );
+        assert_abs_diff_eq!(
+            b.color_at(&Tuple::vector(0., 0., -2.)),
+            Tuple::color(0., 0.25, 0.5)
+        );
+    }
+
+    #[test]
+    fn mapping_directions_to_image_coordinates() {
+        for (direction, u, v) in [
+            (Tuple::vector(0., 0., -1.), 0.5, 0.5),
+            (Tuple::vector(1., 0., 0.), 0.75, 0.5),
+            (Tuple::vector(-1., 0., 0.), 0.25, 0.5),
+            (Tuple::vector(0., 1., -1.), 0.5, 0.25),
+        ] {
+            let (actual_u, actual_v) = EnvironmentMap::uv(&direction);
+            assert_abs_diff_eq!(actual_u, u);
+            assert_abs_diff_eq!(actual_v, v);
+        }
+        assert_eq!(EnvironmentMap::uv(&Tuple::vector(0., 1., 0.)).1, 0.);
+        assert_eq!(EnvironmentMap::uv(&Tuple::vector(0., -1., 0.)).1, 1.);
+    }
+
+    #[test]
+    fn image_coordinates_map_back_to_directions() {
+        let d = Tuple::vector(0.3, -0.4, 0.5).normalize();
+        let (u, v) = EnvironmentMap::uv(&d);
+
+        assert_abs_diff_eq!(EnvironmentMap::direction(u, v), d, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn looking_up_an_environment_map() {
+        let mut image = Canvas::new(4, 2);
+        for x in 0..4 {
+            image.write_pixel(x, 0, Tuple::color(0., 0., 1.));
+            image.write_pixel(x, 1, Tuple::color(0., 1., 0.));
+        }
+        let b = EnvironmentMap::with_intensity(image, 2.);
+
+        assert_eq!(
+            b.color_at(&Tuple::vector(0., 1., 0.)),
+            Tuple::color(0., 0., 2.)
+        );
+        assert_eq!(
+            b.color_at(&Tuple::vector(0., -1., 0.)),
+            Tuple::color(0., 2., 0.)
+        );
+        assert_abs_diff_eq!(
+            b.color_at(&Tuple::vector(1., 0., 0.)),
+            Tuple::color(0., 1., 1.)
+        );
+    }
+
+    #[test]
+    fn an_environment_map_wraps_around_horizontally() {
+        let mut image = Canvas::new(2, 1);
+        image.write_pixel(0, 0, Tuple::color(1., 0., 0.));
+        image.write_pixel(1, 0, Tuple::color(0., 0., 1.));
+        let b = EnvironmentMap::new(image);
+
+        assert_abs_diff_eq!(
+            b.color_at(&Tuple::vector(0., 0., 1.)),
+            Tuple::color(0.5, 0., 0.5)
+        );
+    }
+
+    #[test]
+    fn uniform_background_samples_cover_the_sphere() {
+        let b = Constant::new(Color::WHITE);
+
+        let sample = b.sample((0.3, 0.6)).unwrap();
+
+        assert_abs_diff_eq!(sample.direction.magnitude(), 1.);
+        assert_eq!(sample.radiance, Color::WHITE);
+        assert_abs_diff_eq!(sample.pdf, 1. / (4. * PI));
+    }
+
+    #[test]
+    fn environment_samples_come_from_around_bright_texels() {
+        let mut image = Canvas::new(8, 4);
+        image.write_pixel(3, 1, Tuple::color(5., 5., 5.));
+        let b = EnvironmentMap::new(image);
+        let mut sampler = RandomSampler::new(0);
+
+        for _ in 0..100 {
+            let sample = b.sample(sampler.next_2d()).unwrap();
+            let (u, v) = EnvironmentMap::uv(&sample.direction);
+            assert!((0.25..=0.625).contains(&u), "{u}");
+            assert!((0. ..=0.75).contains(&v), "{v}");
+            assert!(sample.pdf > 1. / (4. * PI));
+        }
+    }
+
+    #[test]
+    fn sampling_an_isolated_bright_texel_converges_to_its_irradiance() {
+        let (width, height) = (16, 8);
+        let mut image = Canvas::new(width, height);
+        image.write_pixel(5, 1, Tuple::color(100., 100., 100.));
+        let b = EnvironmentMap::new(image);
+        let normal = Tuple::vector(0., 1., 0.);
+        let mut sampler = RandomSampler::new(2);
+
+        let n = 50000;
+        let estimate = (0..n)
+            .filter_map(|_| b.sample(sampler.next_2d()))
+            .map(|s| s.radiance.red() * s.direction.dot(&normal).max(0.) / s.pdf)
+            .sum::<Scalar>()
+            / n as Scalar;
+
+        // Bilinear lookups spread the texel into a tent over two texels each way: its
+        // integral across u is one texel width, and across v the tent weights the
+        // cosine-weighted solid angle 2π² sin(πv) cos(πv).
+        let centre = 1.5 / height as Scalar;
+        let a = 1. / height as Scalar;
+        let k = 2. * PI;
+        let across_v = (k * centre).sin() * 2. * (1. - (k * a).cos()) / (k * k * a);
+        let irradiance = 100. * PI * PI / width as Scalar * across_v;
+        assert_abs_diff_eq!(estimate, irradiance, epsilon = 0.02 * irradiance);
+    }
+
+    #[test]
+    fn environment_sample_densities_integrate_to_one() {
+        let mut image = Canvas::new(8, 4);
+        for y in 0..4 {
+            for x in 0..8 {
+                image.write_pixel(x, y, Tuple::color((x + y) as Scalar, 1., 0.5));
+            }
+        }
+        let b = EnvironmentMap::new(image);
+        let mut sampler = RandomSampler::new(1);
+
+        let n = 20000;
+        let area: Scalar = (0..n)
+            .map(|_| 1. / b.sample(sampler.next_2d()).unwrap().pdf)
+            .sum::<Scalar>()
+            / n as Scalar;
+
+        assert_abs_diff_eq!(area, 4. * PI, epsilon = 0.1);
+    }
+
+    #[test]
+    fn a_black_environment_cannot_be_sampled() {
+        let b = EnvironmentMap::new(Canvas::new(4, 2));
+
+        assert!(b.sample((0.5, 0.5)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Environment map image is empty")]
+    fn an_environment_map_needs_pixels() {
+        EnvironmentMap::new(Canvas::new(0, 2));
+    }
+}
@@ -1,10 +1,24 @@
 use crate::{
-    canvas::Canvas,
+    canvas::{Canvas, Rect},
+    integrators::{Integrator, Whitted},
+    intersections::{hit, Computations},
+    passes::{id_color, Pass, Passes},
     rays::Ray,
+    sampling::{RandomSampler, Sampler},
     transformations::Transformation,
-    tuples::{Scalar, Tuple},
+    tuples::{Color, Scalar, Tuple},
     world::World,
 };
+use std::{ops::Range, time::Instant};
+
+mod adaptive;
+mod progress;
+mod progressive;
+
+pub use adaptive::AdaptiveSampling;
+pub use progress::{CancellationToken, Observer, Progress};
+
+const TILE_SIZE: usize = 16;
 
 pub struct Camera {
     hsize: usize,
@@ -14,6 +28,15 @@ pub struct Camera {
     pixel_size: Scalar,
     half_width: Scalar,
     half_height: Scalar,
+    integrator: Box<dyn Integrator>,
+    sampler: Box<dyn Sampler>,
+    samples_per_pixel: usize,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    transparent_background: bool,
+    region: Rect,
+    shutter: (Scalar, Scalar),
+    observer: Option<Observer>,
+    cancellation: Option<CancellationToken>,
 }
 
 impl Camera {
@@ -39,17 +62,112 @@ impl Camera {
             pixel_size: (half_width * 2.) / (hsize as Scalar),
             half_width,
             half_height,
+            integrator: Box::new(Whitted),
+            sampler: Box::new(RandomSampler::new(0)),
+            samples_per_pixel: 1,
+            adaptive_sampling: None,
+            transparent_background: false,
+            region: Rect::new(0, 0, hsize, vsize),
+            shutter: (0., 0.),
+            observer: None,
+            cancellation: None,
         }
     }
 
+    pub fn transform(&self) -> &Transformation {
+        &self.transform
+    }
+
     pub fn set_transform(&mut self, transform: Transformation) {
         self.transform = transform;
         self.inversed_transform = transform.inverse();
     }
 
+    pub fn set_integrator(&mut self, integrator: Box<dyn Integrator>) {
+        self.integrator = integrator;
+    }
+
+    /// Sets the sampler whose copies draw the samples of every pixel. Its seed decides
+    /// the noise of a render.
+    pub fn set_sampler(&mut self, sampler: Box<dyn Sampler>) {
+        self.sampler = sampler;
+    }
+
+    pub fn set_samples_per_pixel(&mut self, samples_per_pixel: usize) {
+        self.samples_per_pixel = samples_per_pixel.max(1);
+    }
+
+    /// Samples every pixel as `adaptive_sampling` asks for instead of with a fixed
+    /// number of samples. Progressive renders keep to fixed sample counts.
+    pub fn set_adaptive_sampling(&mut self, adaptive_sampling: Option<AdaptiveSampling>) {
+        self.adaptive_sampling = adaptive_sampling;
+    }
+
+    /// Makes rays that miss every visible object transparent in the rendered canvas
+    /// instead of showing the world behind them.
+    pub fn set_transparent_background(&mut self, transparent_background: bool) {
+        self.transparent_background = transparent_background;
+    }
+
+    /// Opens the shutter from `open` to `close`, in the time in which moving shapes go
+    /// from their start to their end transform. Every sample is taken at a time in
+    /// between, blurring shapes that move. The shutter is closed at time 0 by default.
+    pub fn set_shutter(&mut self, open: Scalar, close: Scalar) {
+        self.shutter = (open, close);
+    }
+
+    fn shutter_time(&self, sampler: &mut dyn Sampler) -> Scalar {
+        let (open, close) = self.shutter;
+        if open == close {
+            return open;
+        }
+        open + (close - open) * sampler.next_1d()
+    }
+
+    pub fn region(&self) -> Rect {
+        self.region
+    }
+
+    /// Renders only `region` of the image, given in the pixel coordinates of the whole
+    /// image, into a canvas of the size of the region. Every pixel comes out the same
+    /// as in a render of the whole image.
+    pub fn set_region(&mut self, region: Rect) {
+        let bounds = Rect::new(0, 0, self.hsize, self.vsize);
+        assert_eq!(
+            bounds.intersection(&region),
+            region,
+            "Region {region:?} is outside of the image"
+        );
+        self.region = region;
+    }
+
+    /// Calls `observer` after every tile of a render.
+    pub fn set_observer<F>(&mut self, observer: F)
+    where
+        F: Fn(&Progress) + 'static,
+    {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Lets `cancellation` stop renders between tiles. Tiles that were not rendered
+    /// are left transparent.
+    pub fn set_cancellation(&mut self, cancellation: CancellationToken) {
+        self.cancellation = Some(cancellation);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
     fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
-        let xoffset = (px as Scalar + 0.5) * self.pixel_size;
-        let yoffset = (py as Scalar + 0.5) * self.pixel_size;
+        self.ray_through(px as Scalar + 0.5, py as Scalar + 0.5)
+    }
+
+    fn ray_through(&self, x: Scalar, y: Scalar) -> Ray {
+        let xoffset = x * self.pixel_size;
+        let yoffset = y * self.pixel_size;
 
         let world_x = self.half_width - xoffset;
         let world_y = self.half_height - yoffset;
@@ -62,17 +180,185 @@ impl Camera {
     }
 
     pub fn render(&self, world: &World) -> Canvas {
-        let mut image = Canvas::new(self.hsize, self.vsize);
+        self.render_passes(world, &[]).beauty
+    }
 
-        for y in 0..self.vsize {
-            for x in 0..self.hsize {
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(&ray);
-                image.write_pixel(x, y, color);
+    pub fn render_passes(&self, world: &World, passes: &[Pass]) -> Passes {
+        let (width, height) = (self.region.width, self.region.height);
+        let mut image = Canvas::new(width, height);
+        let mut auxiliary: Vec<(Pass, Canvas)> = passes
+            .iter()
+            .map(|pass| (*pass, Canvas::new(width, height)))
+            .collect();
+        let material_ids = if passes.contains(&Pass::MaterialId) {
+            world.material_ids()
+        } else {
+            vec![]
+        };
+
+        let start = Instant::now();
+        let tiles = image.tiles(TILE_SIZE);
+        for (i, tile) in tiles.iter().enumerate() {
+            if self.is_cancelled() {
+                for tile in &tiles[i..] {
+                    for y in tile.y..tile.y + tile.height {
+                        for x in tile.x..tile.x + tile.width {
+                            image.write_alpha(x, y, 0.);
+                        }
+                    }
+                }
+                break;
+            }
+            for y in tile.y..tile.y + tile.height {
+                for x in tile.x..tile.x + tile.width {
+                    let (px, py) = (self.region.x + x, self.region.y + y);
+                    let (color, alpha, samples) = self.pixel_color(world, px, py);
+                    image.write_pixel(x, y, color);
+                    image.write_alpha(x, y, alpha);
+                    if !auxiliary.is_empty() {
+                        self.write_auxiliary(
+                            world,
+                            &material_ids,
+                            &mut auxiliary,
+                            (px, py),
+                            samples,
+                        );
+                    }
+                }
+            }
+            if let Some(observer) = &self.observer {
+                observer(&Progress {
+                    tiles_done: i + 1,
+                    tiles: tiles.len(),
+                    elapsed: start.elapsed(),
+                });
             }
         }
 
-        image
+        Passes::new(image, auxiliary)
+    }
+
+    fn write_auxiliary(
+        &self,
+        world: &World,
+        material_ids: &[usize],
+        auxiliary: &mut [(Pass, Canvas)],
+        (px, py): (usize, usize),
+        samples: usize,
+    ) {
+        let (x, y) = (px - self.region.x, py - self.region.y);
+        for (pass, canvas) in auxiliary.iter_mut() {
+            if *pass == Pass::SampleCount {
+                let samples = samples as Scalar;
+                canvas.write_pixel(x, y, Tuple::color(samples, samples, samples));
+            }
+        }
+
+        let (open, close) = self.shutter;
+        let ray = self.ray_for_pixel(px, py).with_time((open + close) / 2.);
+        let intersections = world.intersect_visible(&ray);
+        let Some(h) = hit(&intersections) else {
+            return;
+        };
+        let comps = Computations::prepare(h, &ray);
+        let index = world.object_index(comps.object).unwrap();
+
+        for (pass, canvas) in auxiliary.iter_mut() {
+            let value = match pass {
+                Pass::SampleCount => continue,
+                Pass::Depth => {
+                    let depth = -(self.transform * comps.point).z;
+                    Tuple::color(depth, depth, depth)
+                }
+                Pass::Normal => Tuple::color(comps.normalv.x, comps.normalv.y, comps.normalv.z),
+                Pass::Albedo => comps.object.material().pattern.pattern_at_shape_time(
+                    comps.object,
+                    &comps.point,
+                    comps.time,
+                ),
+                Pass::ObjectId => id_color(index),
+                Pass::MaterialId => id_color(material_ids[index]),
+                Pass::HitT => Tuple::color(comps.t, comps.t, comps.t),
+            };
+            canvas.write_pixel(x, y, value);
+        }
+    }
+
+    /// Returns the unpremultiplied color of a pixel with its coverage and the number
+    /// of samples taken.
+    fn pixel_color(&self, world: &World, px: usize, py: usize) -> (Color, Scalar, usize) {
+        let mut sampler = self.sampler.clone_box();
+        let (color, covered, samples) = match &self.adaptive_sampling {
+            Some(adaptive) => self.adaptive_samples(world, px, py, adaptive, sampler.as_mut()),
+            None if self.samples_per_pixel == 1 => {
+                return self
+                    .centre_sample(world, px, py, sampler.as_mut())
+                    .map_or((Color::BLACK, 0., 1), |color| (color, 1., 1));
+            }
+            None => {
+                let samples = 0..self.samples_per_pixel;
+                let (color, covered) =
+                    self.jittered_samples(world, px, py, samples, sampler.as_mut());
+                (color, covered, self.samples_per_pixel)
+            }
+        };
+        if covered == 0 {
+            return (Color::BLACK, 0., samples);
+        }
+        (
+            color / covered as Scalar,
+            covered as Scalar / samples as Scalar,
+            samples,
+        )
+    }
+
+    /// The color of the ray through the centre of a pixel, taken as its only sample,
+    /// or `None` when it is transparent.
+    fn centre_sample(
+        &self,
+        world: &World,
+        px: usize,
+        py: usize,
+        sampler: &mut dyn Sampler,
+    ) -> Option<Color> {
+        sampler.start_pixel_sample((px, py), 0);
+        let ray = self
+            .ray_for_pixel(px, py)
+            .with_time(self.shutter_time(sampler));
+        self.sample(world, &ray, sampler)
+    }
+
+    /// Sums the colors of rays jittered within a pixel, one for each sample index,
+    /// returning the sum with the number of rays that were not transparent.
+    fn jittered_samples(
+        &self,
+        world: &World,
+        px: usize,
+        py: usize,
+        samples: Range<usize>,
+        sampler: &mut dyn Sampler,
+    ) -> (Color, usize) {
+        let mut color = Color::BLACK;
+        let mut covered = 0;
+        for index in samples {
+            sampler.start_pixel_sample((px, py), index);
+            let (dx, dy) = sampler.next_2d();
+            let ray = self
+                .ray_through(px as Scalar + dx, py as Scalar + dy)
+                .with_time(self.shutter_time(sampler));
+            if let Some(sample) = self.sample(world, &ray, sampler) {
+                color += sample;
+                covered += 1;
+            }
+        }
+        (color, covered)
+    }
+
+    fn sample(&self, world: &World, ray: &Ray, sampler: &mut dyn Sampler) -> Option<Color> {
+        if self.transparent_background && hit(&world.intersect_visible(ray)).is_none() {
+            return None;
+        }
+        Some(self.integrator.radiance(world, ray, sampler))
     }
 }
 
@@ -80,9 +366,15 @@ impl Camera {
 mod tests {
 
     use super::*;
-    use crate::tuples::Tuple;
+    use crate::{
+        integrators::{AmbientOcclusion, PathTracer},
+        lights::PointLight,
+        sampling::{HaltonSampler, SobolSampler, StratifiedSampler},
+        shapes::Shape,
+        tuples::Tuple,
+    };
     use approx::assert_abs_diff_eq;
-    use std::f64::consts::*;
+    use std::{cell::RefCell, f64::consts::*, rc::Rc};
 
     #[test]
     fn constructing_a_camera() {
@@ -164,4 +456,432 @@ mod tests {
             epsilon = 0.00001
         );
     }
+
+    #[test]
+    fn a_camera_renders_one_direct_lighting_sample_per_pixel_by_default() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        let by_default = c.render(&w);
+
+        c.set_integrator(Box::new(Whitted));
+        let whitted = c.render(&w);
+        c.set_integrator(Box::new(AmbientOcclusion::new(4, 10.)));
+        let occlusion = c.render(&w);
+
+        assert_eq!(c.samples_per_pixel, 1);
+        assert_eq!(by_default.pixels(), whitted.pixels());
+        assert_ne!(by_default.pixels(), occlusion.pixels());
+    }
+
+    #[test]
+    fn rendering_with_multiple_samples_is_repeatable() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_integrator(Box::new(PathTracer::default()));
+        c.set_samples_per_pixel(4);
+
+        let first = c.render(&w);
+        let second = c.render(&w);
+
+        for y in 0..11 {
+            for x in 0..11 {
+                assert_eq!(first.pixel_at(x, y), second.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn multiple_samples_anti_alias_silhouettes() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        let aliased = c.render(&w);
+
+        c.set_samples_per_pixel(16);
+        let smooth = c.render(&w);
+
+        let mut partially_covered = 0;
+        for y in 0..11 {
+            for x in 0..11 {
+                if aliased.pixel_at(x, y) == Color::BLACK && smooth.pixel_at(x, y) != Color::BLACK {
+                    partially_covered += 1;
+                }
+            }
+        }
+        assert!(partially_covered > 0);
+        assert_abs_diff_eq!(
+            smooth.pixel_at(5, 5),
+            aliased.pixel_at(5, 5),
+            epsilon = 0.05
+        );
+    }
+
+    #[test]
+    fn rendering_auxiliary_passes() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+
+        let passes = c.render_passes(
+            &w,
+            &[
+                Pass::Depth,
+                Pass::Normal,
+                Pass::Albedo,
+                Pass::ObjectId,
+                Pass::MaterialId,
+                Pass::HitT,
+            ],
+        );
+
+        let at_center = |pass| passes.get(pass).unwrap().pixel_at(5, 5);
+        assert_abs_diff_eq!(passes.beauty.pixel_at(5, 5), c.render(&w).pixel_at(5, 5));
+        assert_abs_diff_eq!(at_center(Pass::Depth), Tuple::color(4., 4., 4.));
+        assert_abs_diff_eq!(at_center(Pass::Normal), Tuple::color(0., 0., -1.));
+        assert_eq!(at_center(Pass::Albedo), Tuple::color(0.8, 1., 0.6));
+        assert_eq!(at_center(Pass::ObjectId), Tuple::color(1., 1., 1.));
+        assert_eq!(at_center(Pass::MaterialId), Tuple::color(1., 1., 1.));
+        assert_abs_diff_eq!(at_center(Pass::HitT), Tuple::color(4., 4., 4.));
+        for pass in [Pass::Depth, Pass::ObjectId, Pass::HitT] {
+            assert_eq!(passes.get(pass).unwrap().pixel_at(0, 0), Color::BLACK);
+        }
+    }
+
+    #[test]
+    fn depth_is_measured_along_the_view_direction() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+
+        let passes = c.render_passes(&w, &[Pass::Depth, Pass::HitT]);
+
+        let depth = passes.get(Pass::Depth).unwrap().pixel_at(5, 4).red();
+        let t = passes.get(Pass::HitT).unwrap().pixel_at(5, 4).red();
+        assert!(depth > 0.);
+        assert!(depth < t);
+    }
+
+    #[test]
+    fn the_background_is_opaque_by_default() {
+        let w = World::default();
+        let c = Camera::new(11, 11, FRAC_PI_2);
+
+        let image = c.render(&w);
+
+        assert!(image.is_opaque());
+    }
+
+    #[test]
+    fn rendering_with_a_transparent_background() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_transparent_background(true);
+
+        let image = c.render(&w);
+
+        assert_eq!(image.alpha_at(0, 0), 0.);
+        assert_eq!(image.alpha_at(5, 5), 1.);
+        assert_eq!(image.pixel_at(5, 5), c.pixel_color(&w, 5, 5).0);
+    }
+
+    #[test]
+    fn silhouettes_are_partially_transparent() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_transparent_background(true);
+        c.set_samples_per_pixel(16);
+
+        let image = c.render(&w);
+
+        let partial = (0..11)
+            .flat_map(|y| (0..11).map(move |x| (x, y)))
+            .filter(|(x, y)| {
+                let alpha = image.alpha_at(*x, *y);
+                alpha > 0. && alpha < 1.
+            })
+            .count();
+        assert!(partial > 0);
+        assert_eq!(image.alpha_at(5, 5), 1.);
+    }
+
+    #[test]
+    fn an_observer_is_told_about_every_tile() {
+        let w = World::default();
+        let mut c = Camera::new(40, 20, FRAC_PI_2);
+        let reports = Rc::new(RefCell::new(vec![]));
+        let observed = reports.clone();
+        c.set_observer(move |progress| observed.borrow_mut().push(*progress));
+
+        c.render(&w);
+
+        let reports = reports.borrow();
+        assert_eq!(reports.len(), 6);
+        assert!(reports.iter().all(|p| p.tiles == 6));
+        assert_eq!(
+            reports.iter().map(|p| p.tiles_done).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(reports[5].fraction(), 1.);
+        assert_eq!(reports[5].eta(), Some(std::time::Duration::ZERO));
+    }
+
+    #[test]
+    fn a_cancelled_render_is_left_transparent() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        let token = CancellationToken::new();
+        c.set_cancellation(token.clone());
+        token.cancel();
+
+        let image = c.render(&w);
+
+        assert_eq!(image.alpha_at(0, 0), 0.);
+        assert_eq!(image.alpha_at(10, 10), 0.);
+    }
+
+    #[test]
+    fn cancelling_during_a_render_keeps_the_finished_tiles() {
+        let w = World::default();
+        let mut c = Camera::new(40, 20, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        let complete = c.render(&w);
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        c.set_cancellation(token);
+        c.set_observer(move |progress| {
+            if progress.tiles_done == 2 {
+                canceller.cancel();
+            }
+        });
+
+        let partial = c.render(&w);
+
+        assert_eq!(partial.pixel_at(20, 10), complete.pixel_at(20, 10));
+        assert_eq!(partial.alpha_at(31, 15), 1.);
+        assert_eq!(partial.alpha_at(32, 0), 0.);
+        assert_eq!(partial.alpha_at(0, 16), 0.);
+    }
+
+    #[test]
+    fn a_camera_renders_the_whole_image_by_default() {
+        let c = Camera::new(160, 120, FRAC_PI_2);
+
+        assert_eq!(c.region(), Rect::new(0, 0, 160, 120));
+    }
+
+    #[test]
+    fn rendering_a_region_of_the_image() {
+        let w = World::default();
+        let mut c = Camera::new(40, 20, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_samples_per_pixel(4);
+        let passes = [Pass::Depth, Pass::Normal];
+        let whole = c.render_passes(&w, &passes);
+        let region = Rect::new(13, 5, 20, 9);
+
+        c.set_region(region);
+        let part = c.render_passes(&w, &passes);
+
+        assert_eq!(part.beauty.width(), 20);
+        assert_eq!(part.beauty.height(), 9);
+        assert_eq!(part.beauty.pixels(), whole.beauty.crop(region).pixels());
+        for pass in passes {
+            assert_eq!(
+                part.get(pass).unwrap().pixels(),
+                whole.get(pass).unwrap().crop(region).pixels()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the image")]
+    fn a_region_must_lie_within_the_image() {
+        let mut c = Camera::new(40, 20, FRAC_PI_2);
+
+        c.set_region(Rect::new(30, 0, 20, 10));
+    }
+
+    #[test]
+    fn the_sample_count_map_of_a_fixed_sample_count() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_samples_per_pixel(3);
+
+        let passes = c.render_passes(&w, &[Pass::SampleCount]);
+
+        let counts = passes.get(Pass::SampleCount).unwrap();
+        assert_eq!(counts.pixel_at(0, 0), Tuple::color(3., 3., 3.));
+        assert_eq!(counts.pixel_at(5, 5), Tuple::color(3., 3., 3.));
+    }
+
+    #[test]
+    fn adaptive_sampling_spends_samples_on_edges() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_transparent_background(true);
+        c.set_adaptive_sampling(Some(AdaptiveSampling::new(4, 64, 0.01)));
+
+        let passes = c.render_passes(&w, &[Pass::SampleCount]);
+
+        let counts = passes.get(Pass::SampleCount).unwrap();
+        assert_eq!(counts.pixel_at(0, 0).red(), 4.);
+        let edges = (0..11)
+            .flat_map(|y| (0..11).map(move |x| (x, y)))
+            .filter(|(x, y)| {
+                let alpha = passes.beauty.alpha_at(*x, *y);
+                alpha > 0. && alpha < 1.
+            })
+            .collect::<Vec<_>>();
+        assert!(!edges.is_empty());
+        for (x, y) in edges {
+            assert!(counts.pixel_at(x, y).red() > 4.);
+        }
+    }
+
+    #[test]
+    fn adaptive_sampling_stops_at_the_maximum() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_adaptive_sampling(Some(AdaptiveSampling::new(2, 8, 0.)));
+
+        let passes = c.render_passes(&w, &[Pass::SampleCount]);
+
+        let counts = passes.get(Pass::SampleCount).unwrap();
+        for y in 0..11 {
+            for x in 0..11 {
+                assert!(counts.pixel_at(x, y).red() <= 8.);
+            }
+        }
+        assert_eq!(counts.pixel_at(5, 5).red(), 8.);
+        assert_eq!(counts.pixel_at(0, 0).red(), 2.);
+    }
+
+    #[test]
+    fn every_sampler_renders_the_same_pixels_in_any_region() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_integrator(Box::new(PathTracer::default()));
+        c.set_samples_per_pixel(4);
+        let samplers: Vec<Box<dyn Sampler>> = vec![
+            Box::new(RandomSampler::new(1)),
+            Box::new(HaltonSampler::new(1)),
+            Box::new(SobolSampler::new(4, 1)),
+            Box::new(StratifiedSampler::new(2, 2, true, 1)),
+        ];
+        let region = Rect::new(3, 4, 5, 3);
+
+        for sampler in samplers {
+            c.set_sampler(sampler);
+            c.set_region(Rect::new(0, 0, 11, 11));
+            let whole = c.render(&w);
+            c.set_region(region);
+            let part = c.render(&w);
+
+            assert_eq!(part.pixels(), whole.crop(region).pixels());
+            assert_eq!(part.pixels(), c.render(&w).pixels());
+        }
+    }
+
+    #[test]
+    fn the_seed_of_the_sampler_changes_the_noise() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_integrator(Box::new(PathTracer::default()));
+        c.set_samples_per_pixel(2);
+
+        let first = c.render(&w);
+        c.set_sampler(Box::new(RandomSampler::new(1)));
+        let second = c.render(&w);
+
+        assert_ne!(first.pixels(), second.pixels());
+    }
+
+    #[test]
+    fn moving_shapes_are_blurred_while_the_shutter_is_open() {
+        let mut s = Shape::sphere();
+        s.set_motion(
+            Transformation::translation(-2., 0., 0.),
+            Transformation::translation(2., 0., 0.),
+        );
+        let light = PointLight::new(Tuple::point(-10., 10., -10.), Color::WHITE);
+        let w = World::with_objects_and_light(vec![s], light);
+        let mut c = Camera::new(21, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c.set_samples_per_pixel(64);
+        c.set_transparent_background(true);
+
+        let frozen = c.render(&w);
+        c.set_shutter(0., 1.);
+        let blurred = c.render(&w);
+
+        assert_eq!(frozen.alpha_at(10, 5), 0.);
+        assert_eq!(frozen.alpha_at(6, 5), 1.);
+        let streak = blurred.alpha_at(10, 5);
+        assert!(streak > 0.2 && streak < 0.8);
+        assert!(blurred.alpha_at(6, 5) < 1.);
+    }
 }
@@ -0,0 +1,124 @@
+use super::Camera;
+use crate::{
+    sampling::Sampler,
+    tuples::{Color, Scalar},
+    world::World,
+};
+
+/// Settings for sampling a pixel until its estimate is good enough: every pixel takes
+/// `min_samples` samples, then more until the standard error of the mean luminance
+/// drops to `threshold` or `max_samples` are taken.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+    pub min_samples: usize,
+    pub max_samples: usize,
+    pub threshold: Scalar,
+}
+
+impl AdaptiveSampling {
+    /// Raises `min_samples` to two, as the variance needs two samples to be estimated,
+    /// and `max_samples` to `min_samples` when it is smaller.
+    pub fn new(min_samples: usize, max_samples: usize, threshold: Scalar) -> AdaptiveSampling {
+        let min_samples = min_samples.max(2);
+        AdaptiveSampling {
+            min_samples,
+            max_samples: max_samples.max(min_samples),
+            threshold,
+        }
+    }
+}
+
+/// Welford's online mean and variance.
+#[derive(Default)]
+struct RunningVariance {
+    n: usize,
+    mean: Scalar,
+    squares: Scalar,
+}
+
+impl RunningVariance {
+    fn add(&mut self, value: Scalar) {
+        self.n += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.n as Scalar;
+        self.squares += delta * (value - self.mean);
+    }
+
+    fn standard_error(&self) -> Scalar {
+        if self.n < 2 {
+            return Scalar::INFINITY;
+        }
+        (self.squares / ((self.n - 1) * self.n) as Scalar).sqrt()
+    }
+}
+
+impl Camera {
+    /// Like `jittered_samples`, but takes as many samples as `adaptive` asks for and
+    /// returns their number as well. Coverage is refined like luminance, so that
+    /// silhouettes against a transparent background get more samples too.
+    pub(super) fn adaptive_samples(
+        &self,
+        world: &World,
+        px: usize,
+        py: usize,
+        adaptive: &AdaptiveSampling,
+        sampler: &mut dyn Sampler,
+    ) -> (Color, usize, usize) {
+        let mut color = Color::BLACK;
+        let mut covered = 0;
+        let mut luminance = RunningVariance::default();
+        let mut coverage = RunningVariance::default();
+        for n in 1..=adaptive.max_samples {
+            let (sample, hit) = self.jittered_samples(world, px, py, n - 1..n, sampler);
+            color += sample;
+            covered += hit;
+            luminance.add(sample.luminance());
+            coverage.add(hit as Scalar);
+            if n >= adaptive.min_samples
+                && luminance.standard_error() <= adaptive.threshold
+                && coverage.standard_error() <= adaptive.threshold
+            {
+                return (color, covered, n);
+            }
+        }
+        (color, covered, adaptive.max_samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn the_minimum_and_maximum_are_kept_consistent() {
+        let adaptive = AdaptiveSampling::new(0, 1, 0.01);
+
+        assert_eq!(adaptive.min_samples, 2);
+        assert_eq!(adaptive.max_samples, 2);
+
+        let adaptive = AdaptiveSampling::new(1, 8, 0.01);
+
+        assert_eq!(adaptive.min_samples, 2);
+        assert_eq!(adaptive.max_samples, 8);
+
+        let adaptive = AdaptiveSampling::new(6, 3, 0.01);
+
+        assert_eq!(adaptive.min_samples, 6);
+        assert_eq!(adaptive.max_samples, 6);
+    }
+
+    #[test]
+    fn computing_a_running_variance() {
+        let mut variance = RunningVariance::default();
+        assert_eq!(variance.standard_error(), Scalar::INFINITY);
+
+        for value in [2., 4., 4., 4., 5., 5., 7., 9.] {
+            variance.add(value);
+        }
+
+        assert_abs_diff_eq!(variance.mean, 5.);
+        assert_abs_diff_eq!(variance.standard_error(), (32. / 7. / 8. as Scalar).sqrt());
+    }
+}
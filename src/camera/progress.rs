@@ -0,0 +1,97 @@
+use crate::tuples::Scalar;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+pub type Observer = Box<dyn Fn(&Progress)>;
+
+/// How far a render has got, reported after every finished tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub tiles_done: usize,
+    pub tiles: usize,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> Scalar {
+        if self.tiles == 0 {
+            1.
+        } else {
+            self.tiles_done as Scalar / self.tiles as Scalar
+        }
+    }
+
+    /// Remaining time assuming the remaining tiles take as long as the finished ones.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.tiles_done == 0 {
+            return None;
+        }
+        let remaining = (self.tiles - self.tiles_done) as Scalar / self.tiles_done as Scalar;
+        Some(self.elapsed.mul_f64(remaining))
+    }
+}
+
+/// Stops a render between tiles. Clones share the same state, so one can be kept to
+/// cancel a render from elsewhere, e.g. from an observer or another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn estimating_the_remaining_time() {
+        let progress = Progress {
+            tiles_done: 1,
+            tiles: 4,
+            elapsed: Duration::from_secs(2),
+        };
+
+        assert_eq!(progress.fraction(), 0.25);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn there_is_no_estimate_before_the_first_tile() {
+        let progress = Progress {
+            tiles_done: 0,
+            tiles: 4,
+            elapsed: Duration::from_millis(10),
+        };
+
+        assert_eq!(progress.eta(), None);
+    }
+
+    #[test]
+    fn clones_of_a_token_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+}
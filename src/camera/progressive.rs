@@ -0,0 +1,207 @@
+use super::Camera;
+use crate::{
+    canvas::Canvas,
+    tuples::{Color, Scalar},
+    world::World,
+};
+use std::{error::Error, path::Path};
+
+/// Running sums of the samples taken so far for every pixel.
+struct Accumulator {
+    width: usize,
+    color: Vec<Color>,
+    covered: Vec<usize>,
+    samples: usize,
+}
+
+impl Accumulator {
+    fn new(width: usize, height: usize) -> Accumulator {
+        Accumulator {
+            width,
+            color: vec![Color::BLACK; width * height],
+            covered: vec![0; width * height],
+            samples: 0,
+        }
+    }
+
+    fn to_canvas(&self) -> Canvas {
+        let height = self.color.len() / self.width.max(1);
+        let mut canvas = Canvas::new(self.width, height);
+        for y in 0..height {
+            for x in 0..self.width {
+                let i = y * self.width + x;
+                if self.covered[i] == 0 {
+                    canvas.write_pixel(x, y, Color::BLACK);
+                    canvas.write_alpha(x, y, 0.);
+                } else {
+                    canvas.write_pixel(x, y, self.color[i] / self.covered[i] as Scalar);
+                    canvas.write_alpha(x, y, self.covered[i] as Scalar / self.samples as Scalar);
+                }
+            }
+        }
+        canvas
+    }
+}
+
+/// Samples per pixel of pass `pass`, or `None` once they no longer fit in a `usize`.
+fn pass_samples(samples_per_pixel: usize, pass: usize) -> Option<usize> {
+    let factor = 1usize.checked_shl(u32::try_from(pass).ok()?)?;
+    samples_per_pixel.checked_mul(factor)
+}
+
+impl Camera {
+    /// Renders in `passes` passes, each taking twice as many samples per pixel as the
+    /// one before, starting from the camera's samples per pixel. Every pass adds to the
+    /// samples of the previous ones, and the image so far is written to `snapshot`
+    /// after each of them, so a render can be stopped once it looks good enough.
+    /// With one sample per pixel the first pass shoots through pixel centres, as
+    /// `render` does, and later passes jitter.
+    /// Cancellation is checked between passes, and passes stop before their sample
+    /// count would overflow.
+    pub fn render_progressive(
+        &self,
+        world: &World,
+        passes: usize,
+        snapshot: Option<&Path>,
+    ) -> Result<Canvas, Box<dyn Error>> {
+        let region = self.region;
+        let mut accumulator = Accumulator::new(region.width, region.height);
+        let mut sampler = self.sampler.clone_box();
+        for pass in 0..passes.max(1) {
+            if pass > 0 && self.is_cancelled() {
+                break;
+            }
+            let Some(samples) = pass_samples(self.samples_per_pixel, pass) else {
+                break;
+            };
+            let Some(end) = accumulator.samples.checked_add(samples) else {
+                break;
+            };
+            let indices = accumulator.samples..end;
+            for y in 0..region.height {
+                for x in 0..region.width {
+                    let i = y * region.width + x;
+                    let (px, py) = (region.x + x, region.y + y);
+                    let (color, covered) = if self.samples_per_pixel == 1 && pass == 0 {
+                        self.centre_sample(world, px, py, sampler.as_mut())
+                            .map_or((Color::BLACK, 0), |color| (color, 1))
+                    } else {
+                        self.jittered_samples(world, px, py, indices.clone(), sampler.as_mut())
+                    };
+                    accumulator.color[i] += color;
+                    accumulator.covered[i] += covered;
+                }
+            }
+            accumulator.samples += samples;
+            if let Some(path) = snapshot {
+                accumulator.to_canvas().save_to_file(path)?;
+            }
+        }
+        Ok(accumulator.to_canvas())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{
+        canvas::Rect, integrators::PathTracer, transformations::Transformation, tuples::Tuple,
+    };
+    use std::{env, f64::consts::FRAC_PI_2, fs, process};
+
+    fn camera() -> Camera {
+        let mut c = Camera::new(11, 11, FRAC_PI_2);
+        c.set_transform(Transformation::view(
+            &Tuple::point(0., 0., -5.),
+            &Tuple::point(0., 0., 0.),
+            &Tuple::vector(0., 1., 0.),
+        ));
+        c
+    }
+
+    #[test]
+    fn the_first_pass_is_an_ordinary_render() {
+        let w = World::default();
+        let mut c = camera();
+        c.set_integrator(Box::new(PathTracer::default()));
+        for samples_per_pixel in [1, 4] {
+            c.set_samples_per_pixel(samples_per_pixel);
+
+            let progressive = c.render_progressive(&w, 1, None).unwrap();
+
+            assert_eq!(progressive.pixels(), c.render(&w).pixels());
+        }
+    }
+
+    #[test]
+    fn passes_accumulate_samples() {
+        let w = World::default();
+        let mut c = camera();
+        c.set_transparent_background(true);
+
+        let image = c.render_progressive(&w, 3, None).unwrap();
+
+        for y in 0..11 {
+            for x in 0..11 {
+                let covered = image.alpha_at(x, y) * 7.;
+                assert!((covered - covered.round()).abs() < 1e-9);
+            }
+        }
+        assert_eq!(image.alpha_at(5, 5), 1.);
+        assert_eq!(image.alpha_at(0, 0), 0.);
+    }
+
+    #[test]
+    fn more_passes_converge_on_the_same_image() {
+        let w = World::default();
+        let c = camera();
+
+        let one = c.render_progressive(&w, 1, None).unwrap();
+        let four = c.render_progressive(&w, 4, None).unwrap();
+
+        assert!(one.max_difference(&four) > 0.);
+        assert!(one.mse(&four) < 0.01);
+    }
+
+    #[test]
+    fn snapshots_are_written_to_disk() {
+        let w = World::default();
+        let c = camera();
+        let path = env::temp_dir().join(format!(
+            "rust-tracer-{}-progressive-snapshot.ppm",
+            process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let image = c.render_progressive(&w, 2, Some(&path)).unwrap();
+
+        let snapshot = Canvas::load_from_file(&path).unwrap();
+        assert_eq!(snapshot.width(), image.width());
+        assert_eq!(snapshot.height(), image.height());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sample_counts_of_late_passes_do_not_overflow() {
+        assert_eq!(pass_samples(3, 2), Some(12));
+        let last = usize::BITS as usize - 1;
+        assert_eq!(pass_samples(1, last), Some(1 << last));
+        assert_eq!(pass_samples(2, last), None);
+        assert_eq!(pass_samples(1, last + 1), None);
+        assert_eq!(pass_samples(1, usize::MAX), None);
+    }
+
+    #[test]
+    fn rendering_a_region_progressively() {
+        let w = World::default();
+        let mut c = camera();
+        let whole = c.render_progressive(&w, 2, None).unwrap();
+        let region = Rect::new(2, 3, 5, 4);
+
+        c.set_region(region);
+        let part = c.render_progressive(&w, 2, None).unwrap();
+
+        assert_eq!(part.pixels(), whole.crop(region).pixels());
+    }
+}
@@ -1,13 +1,29 @@
-use crate::tuples::{Color, Scalar, Tuple};
+use crate::{
+    tone_mapping::OutputTransform,
+    tuples::{Color, Scalar, Tuple},
+};
 use std::{
     error,
     ffi::OsStr,
-    fmt::Write,
-    fs::File,
-    io::{BufWriter, Write as IOWrite},
+    fmt::{self, Display, Write},
+    fs::{self, File},
+    io::{self, BufWriter, Cursor, Write as IOWrite},
     path::Path,
 };
 
+mod compare;
+mod draw;
+mod font;
+mod hdr;
+mod operations;
+mod pfm;
+mod ppm;
+mod view;
+
+pub use font::{text_size, GLYPH_HEIGHT, GLYPH_WIDTH};
+pub use operations::ResizeFilter;
+pub use view::{Rect, View};
+
 type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 
 struct PpmFormatter {
@@ -57,39 +73,199 @@ impl PpmFormatter {
     }
 }
 
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    UnsupportedFormat(String),
+    InvalidMagicNumber,
+    MalformedHeader(String),
+    TruncatedData,
+    InvalidSample(String),
+    SampleOutOfRange(usize, usize),
+    Png(png::DecodingError),
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "I/O error: {error}"),
+            ImageError::UnsupportedFormat(format) => write!(f, "Unsupported format: {format}"),
+            ImageError::InvalidMagicNumber => write!(f, "Invalid magic number"),
+            ImageError::MalformedHeader(reason) => write!(f, "Malformed header: {reason}"),
+            ImageError::TruncatedData => write!(f, "Image data ends prematurely"),
+            ImageError::InvalidSample(sample) => write!(f, "Invalid sample: {sample}"),
+            ImageError::SampleOutOfRange(value, max) => {
+                write!(f, "Sample {value} exceeds maximum value {max}")
+            }
+            ImageError::Png(error) => write!(f, "PNG error: {error}"),
+        }
+    }
+}
+
+impl error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ImageError::Io(error) => Some(error),
+            ImageError::Png(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(error: io::Error) -> Self {
+        ImageError::Io(error)
+    }
+}
+
+impl From<png::DecodingError> for ImageError {
+    fn from(error: png::DecodingError) -> Self {
+        ImageError::Png(error)
+    }
+}
+
+/// The number of bytes or samples taken by `width` by `height` pixels of
+/// `per_pixel` each, or an error when the header gives a size that cannot exist.
+fn image_length(
+    width: usize,
+    height: usize,
+    per_pixel: usize,
+) -> std::result::Result<usize, ImageError> {
+    width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(per_pixel))
+        .ok_or_else(|| ImageError::MalformedHeader(format!("image too large: {width}x{height}")))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PpmEncoding {
+    #[default]
+    Ascii,
+    Binary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PngBitDepth {
+    #[default]
+    Eight,
+    Sixteen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SaveOptions {
+    pub ppm_encoding: PpmEncoding,
+    pub png_bit_depth: PngBitDepth,
+}
+
+/// Pixels are stored row by row in a single buffer.
+#[derive(Clone)]
 pub struct Canvas {
     width: usize,
     height: usize,
-    pixels: Vec<Vec<Color>>,
+    pixels: Vec<Color>,
+    alpha: Vec<Scalar>,
+    output_transform: OutputTransform,
 }
 
 impl Canvas {
     pub fn new(width: usize, height: usize) -> Canvas {
-        let mut pixels = Vec::with_capacity(height);
-        for _ in 0..height {
-            let mut row = Vec::with_capacity(width);
-            for _ in 0..width {
-                row.push(Tuple::color(0.0, 0.0, 0.0));
-            }
-            pixels.push(row);
-        }
         Canvas {
             width,
             height,
-            pixels,
+            pixels: vec![Tuple::color(0.0, 0.0, 0.0); width * height],
+            alpha: vec![1.; width * height],
+            output_transform: OutputTransform::default(),
         }
     }
 
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.width && y < self.height,
+            "Pixel ({x}, {y}) is outside of a {}x{} canvas",
+            self.width,
+            self.height
+        );
+        y * self.width + x
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
     pub fn pixel_at(&self, x: usize, y: usize) -> Color {
-        self.pixels[y][x]
+        self.pixels[self.index(x, y)]
     }
 
     pub fn write_pixel(&mut self, x: usize, y: usize, c: Color) {
-        self.pixels[y][x] = c;
+        let index = self.index(x, y);
+        self.pixels[index] = c;
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        &mut self.pixels
+    }
+
+    pub fn row(&self, y: usize) -> &[Color] {
+        &self.pixels[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [Color] {
+        &mut self.pixels[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Color]> {
+        self.pixels.chunks_exact(self.width.max(1))
+    }
+
+    /// Coverage of a pixel, with colors stored unpremultiplied. Canvases start out
+    /// fully opaque.
+    pub fn alpha_at(&self, x: usize, y: usize) -> Scalar {
+        self.alpha[self.index(x, y)]
+    }
+
+    pub fn write_alpha(&mut self, x: usize, y: usize, alpha: Scalar) {
+        let index = self.index(x, y);
+        self.alpha[index] = alpha;
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.alpha.iter().all(|alpha| *alpha >= 1.)
     }
 
-    fn scalar_to_u8(value: Scalar) -> u8 {
-        (value * 255.0).round() as u8
+    pub fn output_transform(&self) -> &OutputTransform {
+        &self.output_transform
+    }
+
+    pub fn set_output_transform(&mut self, output_transform: OutputTransform) {
+        self.output_transform = output_transform;
+    }
+
+    fn encode_pixel(&self, x: usize, y: usize, max: u16) -> [u16; 3] {
+        let color = self.output_transform.apply(&self.pixel_at(x, y));
+        let seed = ((y * self.width + x) * 3) as u64;
+        [
+            self.output_transform.quantize(color.red(), max, seed),
+            self.output_transform.quantize(color.green(), max, seed + 1),
+            self.output_transform.quantize(color.blue(), max, seed + 2),
+        ]
+    }
+
+    fn encode_alpha(&self, x: usize, y: usize, max: u16) -> u16 {
+        let max = max as Scalar;
+        (self.alpha_at(x, y) * max).round().clamp(0., max) as u16
+    }
+
+    fn encode_pixel_u8(&self, x: usize, y: usize) -> [u8; 3] {
+        self.encode_pixel(x, y, u8::MAX as u16)
+            .map(|value| value as u8)
     }
 
     pub fn to_ppm(&self) -> Result<String> {
@@ -100,11 +276,11 @@ impl Canvas {
         formatter.new_line()?;
         formatter.write(String::from("255"))?;
         formatter.new_line()?;
-        for row in &self.pixels {
-            for pixel in row {
-                formatter.write(format!("{}", Self::scalar_to_u8(pixel.red())))?;
-                formatter.write(format!("{}", Self::scalar_to_u8(pixel.green())))?;
-                formatter.write(format!("{}", Self::scalar_to_u8(pixel.blue())))?;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for value in self.encode_pixel_u8(x, y) {
+                    formatter.write(format!("{value}"))?;
+                }
             }
             formatter.new_line()?;
         }
@@ -112,46 +288,185 @@ impl Canvas {
         Ok(result)
     }
 
+    pub fn to_ppm_binary(&self) -> Vec<u8> {
+        let mut result = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        result.extend(self.to_rgb_vec());
+        result
+    }
+
     pub fn to_rgb_vec(&self) -> Vec<u8> {
         let mut result = Vec::with_capacity(self.width * self.height * 3);
-        for row in &self.pixels {
-            for pixel in row {
-                result.push(Self::scalar_to_u8(pixel.red()));
-                result.push(Self::scalar_to_u8(pixel.green()));
-                result.push(Self::scalar_to_u8(pixel.blue()));
+        for y in 0..self.height {
+            for x in 0..self.width {
+                result.extend_from_slice(&self.encode_pixel_u8(x, y));
+            }
+        }
+        result
+    }
+
+    /// 16-bit big-endian samples, as stored in PNG files.
+    pub fn to_rgb16_vec(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(self.width * self.height * 6);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for value in self.encode_pixel(x, y, u16::MAX) {
+                    result.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+        }
+        result
+    }
+
+    pub fn to_rgba_vec(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(self.width * self.height * 4);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                result.extend_from_slice(&self.encode_pixel_u8(x, y));
+                result.push(self.encode_alpha(x, y, u8::MAX as u16) as u8);
             }
         }
         result
     }
 
-    fn save_to_ppm(&self, path: &Path) -> Result<()> {
-        let ppm = self.to_ppm()?;
+    pub fn to_rgba16_vec(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(self.width * self.height * 8);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for value in self.encode_pixel(x, y, u16::MAX) {
+                    result.extend_from_slice(&value.to_be_bytes());
+                }
+                result.extend_from_slice(&self.encode_alpha(x, y, u16::MAX).to_be_bytes());
+            }
+        }
+        result
+    }
+
+    fn save_to_ppm(&self, path: &Path, encoding: PpmEncoding) -> Result<()> {
+        match encoding {
+            PpmEncoding::Ascii => Self::save_bytes(path, self.to_ppm()?.as_bytes()),
+            PpmEncoding::Binary => Self::save_bytes(path, &self.to_ppm_binary()),
+        }
+    }
+
+    fn save_bytes(path: &Path, bytes: &[u8]) -> Result<()> {
         let mut file = File::create(path)?;
-        file.write_all(ppm.as_bytes())?;
+        file.write_all(bytes)?;
         Ok(())
     }
 
-    fn save_to_png(&self, path: &Path) -> Result<()> {
+    fn save_to_png(&self, path: &Path, bit_depth: PngBitDepth) -> Result<()> {
         let file = File::create(path)?;
         let w = &mut BufWriter::new(file);
 
         let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
-        encoder.set_color(png::ColorType::Rgb);
-        encoder.set_depth(png::BitDepth::Eight);
+        let opaque = self.is_opaque();
+        encoder.set_color(if opaque {
+            png::ColorType::Rgb
+        } else {
+            png::ColorType::Rgba
+        });
+        let data = match (bit_depth, opaque) {
+            (PngBitDepth::Eight, true) => {
+                encoder.set_depth(png::BitDepth::Eight);
+                self.to_rgb_vec()
+            }
+            (PngBitDepth::Eight, false) => {
+                encoder.set_depth(png::BitDepth::Eight);
+                self.to_rgba_vec()
+            }
+            (PngBitDepth::Sixteen, true) => {
+                encoder.set_depth(png::BitDepth::Sixteen);
+                self.to_rgb16_vec()
+            }
+            (PngBitDepth::Sixteen, false) => {
+                encoder.set_depth(png::BitDepth::Sixteen);
+                self.to_rgba16_vec()
+            }
+        };
         let mut writer = encoder.write_header()?;
-
-        let data = self.to_rgb_vec();
         writer.write_image_data(&data)?;
         Ok(())
     }
 
+    pub fn from_png(data: &[u8]) -> std::result::Result<Canvas, ImageError> {
+        let mut decoder = png::Decoder::new(Cursor::new(data));
+        decoder.set_transformations(png::Transformations::EXPAND);
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![
+            0;
+            reader
+                .output_buffer_size()
+                .ok_or(ImageError::TruncatedData)?
+        ];
+        let info = reader.next_frame(&mut buffer)?;
+
+        let (width, height) = (info.width as usize, info.height as usize);
+        let channels = info.color_type.samples();
+        let samples: Vec<Scalar> = match info.bit_depth {
+            png::BitDepth::Sixteen => buffer[..info.line_size * height]
+                .chunks_exact(2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as Scalar / 65535.)
+                .collect(),
+            _ => buffer[..info.line_size * height]
+                .iter()
+                .map(|byte| *byte as Scalar / 255.)
+                .collect(),
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        for (i, pixel) in samples.chunks_exact(channels).enumerate() {
+            let color = match info.color_type {
+                png::ColorType::Grayscale | png::ColorType::GrayscaleAlpha => {
+                    Tuple::color(pixel[0], pixel[0], pixel[0])
+                }
+                _ => Tuple::color(pixel[0], pixel[1], pixel[2]),
+            };
+            canvas.write_pixel(i % width, i / width, color);
+            match info.color_type {
+                png::ColorType::GrayscaleAlpha => {
+                    canvas.write_alpha(i % width, i / width, pixel[1])
+                }
+                png::ColorType::Rgba => canvas.write_alpha(i % width, i / width, pixel[3]),
+                _ => {}
+            }
+        }
+        Ok(canvas)
+    }
+
+    pub fn load_from_file(path: &Path) -> std::result::Result<Canvas, ImageError> {
+        let extension = path
+            .extension()
+            .ok_or_else(|| ImageError::UnsupportedFormat("unspecified extension".into()))?;
+        if extension == OsStr::new("ppm") {
+            Self::from_ppm(&fs::read(path)?)
+        } else if extension == OsStr::new("png") {
+            Self::from_png(&fs::read(path)?)
+        } else if extension == OsStr::new("hdr") {
+            Self::from_hdr(&fs::read(path)?)
+        } else if extension == OsStr::new("pfm") {
+            Self::from_pfm(&fs::read(path)?)
+        } else {
+            Err(ImageError::UnsupportedFormat(
+                extension.to_string_lossy().into(),
+            ))
+        }
+    }
+
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        self.save_to_file_with_options(path, &SaveOptions::default())
+    }
+
+    pub fn save_to_file_with_options(&self, path: &Path, options: &SaveOptions) -> Result<()> {
         match path.extension() {
             Some(ext) => {
                 if ext == OsStr::new("ppm") {
-                    self.save_to_ppm(path)
+                    self.save_to_ppm(path, options.ppm_encoding)
                 } else if ext == OsStr::new("png") {
-                    self.save_to_png(path)
+                    self.save_to_png(path, options.png_bit_depth)
+                } else if ext == OsStr::new("hdr") {
+                    Self::save_bytes(path, &self.to_hdr())
+                } else if ext == OsStr::new("pfm") {
+                    Self::save_bytes(path, &self.to_pfm())
                 } else {
                     Err(format!("Unsupported extension: {ext:?}").into())
                 }
@@ -165,6 +480,29 @@ impl Canvas {
 mod tests {
 
     use super::*;
+    use crate::tone_mapping::ToneMapping;
+
+    /// A canvas whose pixel (x, y) has the color `color(x, y)`.
+    pub(super) fn canvas_from(
+        width: usize,
+        height: usize,
+        color: impl Fn(usize, usize) -> Color,
+    ) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                c.write_pixel(x, y, color(x, y));
+            }
+        }
+        c
+    }
+
+    /// Pixel (x, y) has red x and green y, so every pixel can be told apart.
+    pub(super) fn numbered(width: usize, height: usize) -> Canvas {
+        canvas_from(width, height, |x, y| {
+            Tuple::color(x as Scalar, y as Scalar, 0.)
+        })
+    }
 
     #[test]
     fn creating_canvas() {
@@ -264,4 +602,199 @@ mod tests {
             vec![255, 0, 0, 0, 255, 0, 255, 255, 255, 0, 0, 255, 255, 255, 0, 0, 0, 0]
         );
     }
+
+    #[test]
+    fn output_transform_is_applied_when_converting_to_u8() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Tuple::color(0.5, 1., 3.));
+        c.write_pixel(1, 0, Tuple::color(0.214041, 0., 0.));
+        c.set_output_transform(OutputTransform {
+            tone_mapping: ToneMapping::Reinhard,
+            srgb: true,
+            ..Default::default()
+        });
+
+        let data = c.to_rgb_vec();
+
+        assert_eq!(data, vec![156, 188, 225, 117, 0, 0]);
+    }
+
+    #[test]
+    fn output_transform_is_applied_to_ppm() {
+        let mut c = Canvas::new(1, 1);
+        c.write_pixel(0, 0, Tuple::color(0.25, 0.5, 2.));
+        c.set_output_transform(OutputTransform {
+            exposure: 1.,
+            ..Default::default()
+        });
+
+        let ppm = c.to_ppm().unwrap();
+
+        assert_eq!(ppm.lines().nth(3), Some("128 255 255"));
+    }
+
+    #[test]
+    fn constructing_binary_ppm() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Tuple::color(1., 0.5, 0.));
+        c.write_pixel(1, 0, Tuple::color(0., 0., 1.));
+
+        let ppm = c.to_ppm_binary();
+
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend([255, 128, 0, 0, 0, 255]);
+        assert_eq!(ppm, expected);
+    }
+
+    #[test]
+    fn convert_to_u16_array() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Tuple::color(1., 0.5, 0.));
+        c.write_pixel(1, 0, Tuple::color(0., 0., 0.25));
+
+        let data = c.to_rgb16_vec();
+
+        assert_eq!(
+            data,
+            vec![0xff, 0xff, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00]
+        );
+    }
+
+    fn temporary_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rust-tracer-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn saving_and_loading_png_files() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(0, 0, Tuple::color(1., 0., 0.));
+        c.write_pixel(2, 1, Tuple::color(0.2, 0.4, 0.6));
+
+        for bit_depth in [PngBitDepth::Eight, PngBitDepth::Sixteen] {
+            let path = temporary_path(&format!("{bit_depth:?}.png"));
+            let options = SaveOptions {
+                png_bit_depth: bit_depth,
+                ..Default::default()
+            };
+            c.save_to_file_with_options(&path, &options).unwrap();
+
+            let loaded = Canvas::load_from_file(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded.width(), 3);
+            assert_eq!(loaded.height(), 2);
+            assert_eq!(loaded.pixel_at(0, 0), Tuple::color(1., 0., 0.));
+            approx::assert_abs_diff_eq!(
+                loaded.pixel_at(2, 1),
+                Tuple::color(0.2, 0.4, 0.6),
+                epsilon = 0.002
+            );
+        }
+    }
+
+    #[test]
+    fn saving_and_loading_ppm_files() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(1, 1, Tuple::color(0.2, 0.4, 0.6));
+
+        for encoding in [PpmEncoding::Ascii, PpmEncoding::Binary] {
+            let path = temporary_path(&format!("{encoding:?}.ppm"));
+            let options = SaveOptions {
+                ppm_encoding: encoding,
+                ..Default::default()
+            };
+            c.save_to_file_with_options(&path, &options).unwrap();
+
+            let loaded = Canvas::load_from_file(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded.pixel_at(1, 1), Tuple::color(0.2, 0.4, 0.6));
+        }
+    }
+
+    #[test]
+    fn loading_an_unsupported_format() {
+        let result = Canvas::load_from_file(Path::new("image.tga"));
+
+        assert!(matches!(result, Err(ImageError::UnsupportedFormat(_))));
+    }
+
+    #[test]
+    fn loading_a_missing_file() {
+        let result = Canvas::load_from_file(&temporary_path("missing.ppm"));
+
+        assert!(matches!(result, Err(ImageError::Io(_))));
+    }
+
+    #[test]
+    fn loading_malformed_png_data() {
+        let result = Canvas::from_png(b"not a png");
+
+        assert!(matches!(result, Err(ImageError::Png(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of a 2x2 canvas")]
+    fn writing_past_the_end_of_a_row() {
+        let mut c = Canvas::new(2, 2);
+
+        c.write_pixel(2, 0, Color::WHITE);
+    }
+
+    #[test]
+    fn a_new_canvas_is_opaque() {
+        let c = Canvas::new(2, 2);
+
+        assert_eq!(c.alpha_at(1, 1), 1.);
+        assert!(c.is_opaque());
+    }
+
+    #[test]
+    fn writing_alpha_values() {
+        let mut c = Canvas::new(2, 1);
+
+        c.write_alpha(1, 0, 0.25);
+
+        assert_eq!(c.alpha_at(0, 0), 1.);
+        assert_eq!(c.alpha_at(1, 0), 0.25);
+        assert!(!c.is_opaque());
+    }
+
+    #[test]
+    fn encoding_rgba_data() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Tuple::color(1., 0.5, 0.));
+        c.write_alpha(0, 0, 0.5);
+        c.write_alpha(1, 0, 0.);
+
+        assert_eq!(c.to_rgba_vec(), vec![255, 128, 0, 128, 0, 0, 0, 0]);
+        assert_eq!(
+            c.to_rgba16_vec(),
+            vec![255, 255, 128, 0, 0, 0, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn saving_and_loading_transparent_png_files() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Tuple::color(0., 1., 0.));
+        c.write_alpha(0, 0, 0.6);
+        c.write_alpha(1, 0, 0.);
+
+        for bit_depth in [PngBitDepth::Eight, PngBitDepth::Sixteen] {
+            let path = temporary_path(&format!("transparent-{bit_depth:?}.png"));
+            let options = SaveOptions {
+                png_bit_depth: bit_depth,
+                ..Default::default()
+            };
+            c.save_to_file_with_options(&path, &options).unwrap();
+
+            let loaded = Canvas::load_from_file(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded.pixel_at(0, 0), Tuple::color(0., 1., 0.));
+            assert_eq!(loaded.alpha_at(0, 0), 0.6);
+            assert_eq!(loaded.alpha_at(1, 0), 0.);
+        }
+    }
 }
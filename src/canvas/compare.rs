@@ -0,0 +1,246 @@
+use super::Canvas;
+use crate::tuples::{Color, Scalar, Tuple};
+
+const SSIM_RADIUS: isize = 5;
+const SSIM_SIGMA: Scalar = 1.5;
+const SSIM_C1: Scalar = 0.01 * 0.01;
+const SSIM_C2: Scalar = 0.03 * 0.03;
+
+impl Canvas {
+    fn assert_same_size(&self, other: &Canvas) {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Canvases differ in size"
+        );
+    }
+
+    fn pairs<'a>(&'a self, other: &'a Canvas) -> impl Iterator<Item = (Color, Color)> + 'a {
+        self.assert_same_size(other);
+        self.pixels
+            .iter()
+            .zip(other.pixels.iter())
+            .map(|(a, b)| (*a, *b))
+    }
+
+    /// Absolute difference of the stored values per channel. Like the other
+    /// comparisons, it needs canvases of the same size.
+    pub fn difference(&self, other: &Canvas) -> Canvas {
+        self.assert_same_size(other);
+        let mut result = Canvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let d = self.pixel_at(x, y) - other.pixel_at(x, y);
+                result.write_pixel(
+                    x,
+                    y,
+                    Tuple::color(d.red().abs(), d.green().abs(), d.blue().abs()),
+                );
+            }
+        }
+        result
+    }
+
+    /// The largest difference in any channel of any pixel.
+    pub fn max_difference(&self, other: &Canvas) -> Scalar {
+        self.pairs(other)
+            .map(|(a, b)| max_channel(&(a - b)))
+            .fold(0., Scalar::max)
+    }
+
+    pub fn mse(&self, other: &Canvas) -> Scalar {
+        let samples = (self.width * self.height * 3).max(1) as Scalar;
+        self.pairs(other)
+            .map(|(a, b)| {
+                let d = a - b;
+                d.dot(&d)
+            })
+            .sum::<Scalar>()
+            / samples
+    }
+
+    /// Peak signal-to-noise ratio in decibels for a peak value of 1, infinite for
+    /// identical canvases.
+    pub fn psnr(&self, other: &Canvas) -> Scalar {
+        -10. * self.mse(other).log10()
+    }
+
+    /// Mean structural similarity of luminance over Gaussian windows, 1 for identical
+    /// canvases.
+    pub fn ssim(&self, other: &Canvas) -> Scalar {
+        self.assert_same_size(other);
+        if self.width == 0 || self.height == 0 {
+            return 1.;
+        }
+        let a = self.luminance_plane();
+        let b = other.luminance_plane();
+        let weights: Vec<Scalar> = (-SSIM_RADIUS..=SSIM_RADIUS)
+            .map(|i| (-((i * i) as Scalar) / (2. * SSIM_SIGMA * SSIM_SIGMA)).exp())
+            .collect();
+
+        let mut total = 0.;
+        for y in 0..self.height as isize {
+            for x in 0..self.width as isize {
+                let (mut weight_sum, mut mean_a, mut mean_b) = (0., 0., 0.);
+                let (mut square_a, mut square_b, mut product) = (0., 0., 0.);
+                for dy in -SSIM_RADIUS..=SSIM_RADIUS {
+                    for dx in -SSIM_RADIUS..=SSIM_RADIUS {
+                        let (sx, sy) = (x + dx, y + dy);
+                        if sx < 0
+                            || sy < 0
+                            || sx >= self.width as isize
+                            || sy >= self.height as isize
+                        {
+                            continue;
+                        }
+                        let w = weights[(dx + SSIM_RADIUS) as usize]
+                            * weights[(dy + SSIM_RADIUS) as usize];
+                        let i = sy as usize * self.width + sx as usize;
+                        weight_sum += w;
+                        mean_a += w * a[i];
+                        mean_b += w * b[i];
+                        square_a += w * a[i] * a[i];
+                        square_b += w * b[i] * b[i];
+                        product += w * a[i] * b[i];
+                    }
+                }
+                mean_a /= weight_sum;
+                mean_b /= weight_sum;
+                let variance_a = square_a / weight_sum - mean_a * mean_a;
+                let variance_b = square_b / weight_sum - mean_b * mean_b;
+                let covariance = product / weight_sum - mean_a * mean_b;
+                total += ((2. * mean_a * mean_b + SSIM_C1) * (2. * covariance + SSIM_C2))
+                    / ((mean_a * mean_a + mean_b * mean_b + SSIM_C1)
+                        * (variance_a + variance_b + SSIM_C2));
+            }
+        }
+        total / (self.width * self.height) as Scalar
+    }
+
+    /// Largest channel difference per pixel, running from black through red and
+    /// yellow to white at `max_difference` and above.
+    pub fn heat_map(&self, other: &Canvas, max_difference: Scalar) -> Canvas {
+        self.assert_same_size(other);
+        let mut result = Canvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let d = max_channel(&(self.pixel_at(x, y) - other.pixel_at(x, y)));
+                result.write_pixel(x, y, heat(d / max_difference));
+            }
+        }
+        result
+    }
+
+    fn luminance_plane(&self) -> Vec<Scalar> {
+        self.pixels.iter().map(|color| color.luminance()).collect()
+    }
+}
+
+fn max_channel(color: &Color) -> Scalar {
+    color
+        .red()
+        .abs()
+        .max(color.green().abs())
+        .max(color.blue().abs())
+}
+
+fn heat(t: Scalar) -> Color {
+    let t = if t.is_nan() { 1. } else { t.clamp(0., 1.) };
+    Tuple::color(
+        (3. * t).min(1.),
+        (3. * t - 1.).clamp(0., 1.),
+        (3. * t - 2.).max(0.),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::canvas::tests::canvas_from;
+    use approx::assert_abs_diff_eq;
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        canvas_from(width, height, |x, y| {
+            let v = (x + y) as Scalar / (width + height) as Scalar;
+            Tuple::color(v, 1. - v, 0.5)
+        })
+    }
+
+    #[test]
+    fn the_difference_between_canvases() {
+        let mut a = Canvas::new(2, 1);
+        let mut b = Canvas::new(2, 1);
+        a.write_pixel(0, 0, Tuple::color(0.5, 0.2, 1.));
+        b.write_pixel(0, 0, Tuple::color(0.25, 0.4, 1.));
+
+        let d = a.difference(&b);
+
+        assert_abs_diff_eq!(d.pixel_at(0, 0), Tuple::color(0.25, 0.2, 0.));
+        assert_eq!(d.pixel_at(1, 0), Color::BLACK);
+        assert_abs_diff_eq!(a.max_difference(&b), 0.25);
+    }
+
+    #[test]
+    fn mean_squared_error_and_psnr() {
+        let a = Canvas::new(2, 2);
+        let mut b = Canvas::new(2, 2);
+        b.write_pixel(1, 1, Tuple::color(0.6, 0.6, 0.6));
+
+        assert_abs_diff_eq!(a.mse(&b), 0.09);
+        assert_abs_diff_eq!(a.psnr(&b), 10.457575, epsilon = 0.000001);
+    }
+
+    #[test]
+    fn identical_canvases_are_a_perfect_match() {
+        let a = gradient(16, 12);
+
+        assert_eq!(a.mse(&a), 0.);
+        assert_eq!(a.psnr(&a), Scalar::INFINITY);
+        assert_abs_diff_eq!(a.ssim(&a), 1., epsilon = 1e-12);
+        assert_eq!(a.heat_map(&a, 0.1).pixel_at(3, 3), Color::BLACK);
+    }
+
+    #[test]
+    fn structural_similarity_drops_with_noise() {
+        let a = gradient(16, 12);
+        let mut slightly = gradient(16, 12);
+        let mut badly = gradient(16, 12);
+        for y in 0..12 {
+            for x in 0..16 {
+                let sign = if (x + y) % 2 == 0 { 1. } else { -1. };
+                let c = a.pixel_at(x, y);
+                slightly.write_pixel(x, y, c + Tuple::color(0.02, 0.02, 0.02) * sign);
+                badly.write_pixel(x, y, c + Tuple::color(0.3, 0.3, 0.3) * sign);
+            }
+        }
+
+        let slight = a.ssim(&slightly);
+        let bad = a.ssim(&badly);
+
+        assert!(slight < 1.);
+        assert!(bad < slight);
+        assert!(bad > -1.);
+    }
+
+    #[test]
+    fn a_heat_map_of_differences() {
+        let a = Canvas::new(3, 1);
+        let mut b = Canvas::new(3, 1);
+        b.write_pixel(0, 0, Tuple::color(0., 0.05, 0.));
+        b.write_pixel(1, 0, Tuple::color(0.2, 0., 0.));
+        b.write_pixel(2, 0, Tuple::color(0., 0., 0.1));
+
+        let h = a.heat_map(&b, 0.1);
+
+        assert_abs_diff_eq!(h.pixel_at(0, 0), Tuple::color(1., 0.5, 0.));
+        assert_eq!(h.pixel_at(1, 0), Color::WHITE);
+        assert_abs_diff_eq!(h.pixel_at(2, 0), Color::WHITE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Canvases differ in size")]
+    fn comparing_canvases_of_different_sizes() {
+        Canvas::new(2, 1).mse(&Canvas::new(1, 2));
+    }
+}
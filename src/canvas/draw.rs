@@ -0,0 +1,390 @@
+use super::{operations::over, Canvas, Rect};
+use crate::tuples::{Color, Scalar};
+
+impl Canvas {
+    /// Blends `color` over a pixel in proportion to how much of it is covered.
+    pub(super) fn blend_pixel(&mut self, x: isize, y: isize, color: Color, coverage: Scalar) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let coverage = coverage.clamp(0., 1.);
+        if coverage <= 0. {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let (blended, alpha) = over(self.pixel_at(x, y), self.alpha_at(x, y), color, coverage);
+        self.write_pixel(x, y, blended);
+        self.write_alpha(x, y, alpha);
+    }
+
+    /// One pixel wide anti-aliased line drawn with Wu's algorithm, in pixel coordinates
+    /// where pixel (x, y) covers the square from (x, y) to (x + 1, y + 1). Only the
+    /// part near the canvas is walked, and lines with non-finite ends are not drawn.
+    pub fn draw_line(&mut self, from: (Scalar, Scalar), to: (Scalar, Scalar), color: Color) {
+        if ![from.0, from.1, to.0, to.1].iter().all(|v| v.is_finite()) {
+            return;
+        }
+        // The margin keeps the ends of a clipped line, which Wu's algorithm fades,
+        // off the canvas.
+        let margin = 2.;
+        let Some((from, to)) = clip_line(
+            from,
+            to,
+            (-margin, -margin),
+            (
+                self.width as Scalar + margin,
+                self.height as Scalar + margin,
+            ),
+        ) else {
+            return;
+        };
+        let (mut x0, mut y0) = (from.0 - 0.5, from.1 - 0.5);
+        let (mut x1, mut y1) = (to.0 - 0.5, to.1 - 0.5);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            (x0, y0, x1, y1) = (y0, x0, y1, x1);
+        }
+        if x0 > x1 {
+            (x0, y0, x1, y1) = (x1, y1, x0, y0);
+        }
+        let mut plot = |x: Scalar, y: Scalar, coverage: Scalar| {
+            let (x, y) = if steep { (y, x) } else { (x, y) };
+            self.blend_pixel(x as isize, y as isize, color, coverage);
+        };
+        let fpart = |v: Scalar| v - v.floor();
+        let rfpart = |v: Scalar| 1. - fpart(v);
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0. { 1. } else { (y1 - y0) / dx };
+
+        let xend = (x0 + 0.5).floor();
+        let yend = y0 + gradient * (xend - x0);
+        let xgap = rfpart(x0 + 0.5);
+        let start = xend;
+        plot(start, yend.floor(), rfpart(yend) * xgap);
+        plot(start, yend.floor() + 1., fpart(yend) * xgap);
+        let mut intery = yend + gradient;
+
+        let xend = (x1 + 0.5).floor();
+        let yend = y1 + gradient * (xend - x1);
+        let xgap = fpart(x1 + 0.5);
+        let end = xend;
+        if end > start {
+            plot(end, yend.floor(), rfpart(yend) * xgap);
+            plot(end, yend.floor() + 1., fpart(yend) * xgap);
+        }
+
+        let mut x = start + 1.;
+        while x < end {
+            plot(x, intery.floor(), rfpart(intery));
+            plot(x, intery.floor() + 1., fpart(intery));
+            intery += gradient;
+            x += 1.;
+        }
+    }
+
+    /// Visits every pixel of the canvas whose square may overlap the given bounds.
+    fn for_pixels_within<F>(&mut self, min: (Scalar, Scalar), max: (Scalar, Scalar), mut f: F)
+    where
+        F: FnMut(&mut Canvas, isize, isize),
+    {
+        if ![min.0, min.1, max.0, max.1].iter().all(|v| v.is_finite()) {
+            return;
+        }
+        let last = (self.width as Scalar - 1., self.height as Scalar - 1.);
+        let x_range = (min.0.floor().max(0.) as isize)..=(max.0.ceil().min(last.0) as isize);
+        let y_range = (min.1.floor().max(0.) as isize)..=(max.1.ceil().min(last.1) as isize);
+        for y in y_range {
+            for x in x_range.clone() {
+                f(self, x, y);
+            }
+        }
+    }
+
+    /// One pixel wide anti-aliased outline of a circle.
+    pub fn draw_circle(&mut self, center: (Scalar, Scalar), radius: Scalar, color: Color) {
+        let min = (center.0 - radius - 1., center.1 - radius - 1.);
+        let max = (center.0 + radius + 1., center.1 + radius + 1.);
+        self.for_pixels_within(min, max, |canvas, x, y| {
+            let distance = distance_to_pixel(center, x, y);
+            canvas.blend_pixel(x, y, color, 1. - (distance - radius).abs());
+        });
+    }
+
+    /// Fills a circle, blending its edge pixels by coverage.
+    pub fn fill_circle(&mut self, center: (Scalar, Scalar), radius: Scalar, color: Color) {
+        let min = (center.0 - radius - 1., center.1 - radius - 1.);
+        let max = (center.0 + radius + 1., center.1 + radius + 1.);
+        self.for_pixels_within(min, max, |canvas, x, y| {
+            let distance = distance_to_pixel(center, x, y);
+            canvas.blend_pixel(x, y, color, radius - distance + 0.5);
+        });
+    }
+
+    /// Paints whole pixels, replacing what was there.
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let area = self.bounds().intersection(&rect);
+        for y in area.y..area.y + area.height {
+            for x in area.x..area.x + area.width {
+                self.write_pixel(x, y, color);
+                self.write_alpha(x, y, 1.);
+            }
+        }
+    }
+
+    /// Fills a polygon by the even-odd rule, estimating coverage from 4x4 samples per
+    /// pixel.
+    pub fn fill_polygon(&mut self, points: &[(Scalar, Scalar)], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+        let min = points
+            .iter()
+            .fold((Scalar::INFINITY, Scalar::INFINITY), |m, p| {
+                (m.0.min(p.0), m.1.min(p.1))
+            });
+        let max = points
+            .iter()
+            .fold((Scalar::NEG_INFINITY, Scalar::NEG_INFINITY), |m, p| {
+                (m.0.max(p.0), m.1.max(p.1))
+            });
+        self.for_pixels_within(min, max, |canvas, x, y| {
+            let mut inside = 0;
+            for i in 0..POLYGON_SAMPLES {
+                for j in 0..POLYGON_SAMPLES {
+                    let point = (
+                        x as Scalar + (j as Scalar + 0.5) / POLYGON_SAMPLES as Scalar,
+                        y as Scalar + (i as Scalar + 0.5) / POLYGON_SAMPLES as Scalar,
+                    );
+                    if contains(points, point) {
+                        inside += 1;
+                    }
+                }
+            }
+            let coverage = inside as Scalar / (POLYGON_SAMPLES * POLYGON_SAMPLES) as Scalar;
+            canvas.blend_pixel(x, y, color, coverage);
+        });
+    }
+}
+
+const POLYGON_SAMPLES: usize = 4;
+
+/// Liang-Barsky clipping of a segment to a box, or `None` when it misses the box.
+fn clip_line(
+    from: (Scalar, Scalar),
+    to: (Scalar, Scalar),
+    min: (Scalar, Scalar),
+    max: (Scalar, Scalar),
+) -> Option<((Scalar, Scalar), (Scalar, Scalar))> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let (mut t0, mut t1): (Scalar, Scalar) = (0., 1.);
+    for (p, q) in [
+        (-dx, from.0 - min.0),
+        (dx, max.0 - from.0),
+        (-dy, from.1 - min.1),
+        (dy, max.1 - from.1),
+    ] {
+        if p == 0. {
+            if q < 0. {
+                return None;
+            }
+        } else if p < 0. {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+    Some((
+        (from.0 + t0 * dx, from.1 + t0 * dy),
+        (from.0 + t1 * dx, from.1 + t1 * dy),
+    ))
+}
+
+fn distance_to_pixel(center: (Scalar, Scalar), x: isize, y: isize) -> Scalar {
+    let dx = x as Scalar + 0.5 - center.0;
+    let dy = y as Scalar + 0.5 - center.1;
+    (dx * dx + dy * dy).sqrt()
+}
+
+fn contains(points: &[(Scalar, Scalar)], (x, y): (Scalar, Scalar)) -> bool {
+    let mut inside = false;
+    let mut previous = points[points.len() - 1];
+    for current in points {
+        if (current.1 > y) != (previous.1 > y)
+            && x < (previous.0 - current.0) * (y - current.1) / (previous.1 - current.1) + current.0
+        {
+            inside = !inside;
+        }
+        previous = *current;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tuples::Tuple;
+    use approx::assert_abs_diff_eq;
+
+    fn coverage(canvas: &Canvas, x: usize, y: usize) -> Scalar {
+        canvas.pixel_at(x, y).red()
+    }
+
+    #[test]
+    fn drawing_a_horizontal_line() {
+        let mut c = Canvas::new(6, 3);
+
+        c.draw_line((0.5, 1.5), (4.5, 1.5), Color::WHITE);
+
+        assert_eq!(coverage(&c, 0, 1), 0.5);
+        for x in 1..4 {
+            assert_eq!(c.pixel_at(x, 1), Color::WHITE);
+            assert_eq!(c.pixel_at(x, 0), Color::BLACK);
+            assert_eq!(c.pixel_at(x, 2), Color::BLACK);
+        }
+        assert_eq!(coverage(&c, 4, 1), 0.5);
+        assert_eq!(c.pixel_at(5, 1), Color::BLACK);
+    }
+
+    #[test]
+    fn a_line_between_pixel_rows_is_shared_between_them() {
+        let mut c = Canvas::new(5, 3);
+
+        c.draw_line((0.5, 1.), (4.5, 1.), Color::WHITE);
+
+        assert_eq!(coverage(&c, 2, 0), 0.5);
+        assert_eq!(coverage(&c, 2, 1), 0.5);
+        assert_eq!(coverage(&c, 2, 2), 0.);
+    }
+
+    #[test]
+    fn drawing_a_steep_line_in_either_direction() {
+        let mut down = Canvas::new(3, 6);
+        let mut up = Canvas::new(3, 6);
+
+        down.draw_line((1.5, 0.5), (1.5, 4.5), Color::WHITE);
+        up.draw_line((1.5, 4.5), (1.5, 0.5), Color::WHITE);
+
+        for y in 1..4 {
+            assert_eq!(down.pixel_at(1, y), Color::WHITE);
+            assert_eq!(down.pixel_at(0, y), Color::BLACK);
+        }
+        assert_eq!(down.pixels(), up.pixels());
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_canvas() {
+        let mut c = Canvas::new(3, 3);
+
+        c.draw_line((-5., -5.), (10., 10.), Color::WHITE);
+
+        assert!(coverage(&c, 1, 1) > 0.9);
+    }
+
+    #[test]
+    fn lines_far_outside_the_canvas_are_clipped_before_drawing() {
+        let mut c = Canvas::new(4, 4);
+
+        c.draw_line((-1e12, 2.5), (1e12, 2.5), Color::WHITE);
+        c.draw_line((-1e12, -1e12), (-1e12, 1e12), Color::WHITE);
+
+        for x in 0..4 {
+            assert_eq!(c.pixel_at(x, 2), Color::WHITE);
+            assert_eq!(c.pixel_at(x, 1), Color::BLACK);
+        }
+    }
+
+    #[test]
+    fn shapes_with_non_finite_coordinates_are_not_drawn() {
+        let mut c = Canvas::new(4, 4);
+
+        c.draw_line((Scalar::NAN, 0.), (3., 3.), Color::WHITE);
+        c.draw_line((0., 0.), (Scalar::INFINITY, 3.), Color::WHITE);
+        c.fill_circle((Scalar::NAN, 2.), 1., Color::WHITE);
+        c.fill_polygon(&[(0., 0.), (4., Scalar::NAN), (0., 4.)], Color::WHITE);
+
+        assert!(c.pixels().iter().all(|pixel| *pixel == Color::BLACK));
+    }
+
+    #[test]
+    fn a_huge_circle_only_visits_the_canvas() {
+        let mut c = Canvas::new(4, 4);
+
+        c.fill_circle((2., 2.), 1e12, Color::WHITE);
+
+        assert!(c.pixels().iter().all(|pixel| *pixel == Color::WHITE));
+    }
+
+    #[test]
+    fn drawing_a_circle() {
+        let mut c = Canvas::new(11, 11);
+
+        c.draw_circle((5.5, 5.5), 4., Color::WHITE);
+
+        assert_eq!(c.pixel_at(5, 1), Color::WHITE);
+        assert_eq!(c.pixel_at(9, 5), Color::WHITE);
+        assert_eq!(c.pixel_at(5, 5), Color::BLACK);
+        assert_eq!(c.pixel_at(0, 0), Color::BLACK);
+    }
+
+    #[test]
+    fn filling_a_circle() {
+        let mut c = Canvas::new(11, 11);
+
+        c.fill_circle((5.5, 5.5), 3., Color::WHITE);
+
+        assert_eq!(c.pixel_at(5, 5), Color::WHITE);
+        assert_eq!(c.pixel_at(5, 3), Color::WHITE);
+        assert_eq!(coverage(&c, 5, 2), 0.5);
+        assert_eq!(c.pixel_at(5, 1), Color::BLACK);
+    }
+
+    #[test]
+    fn filling_a_rectangle() {
+        let mut c = Canvas::new(4, 4);
+        c.write_alpha(3, 3, 0.);
+
+        c.fill_rect(Rect::new(2, 1, 5, 5), Tuple::color(1., 0., 0.));
+
+        assert_eq!(c.pixel_at(2, 1), Tuple::color(1., 0., 0.));
+        assert_eq!(c.pixel_at(3, 3), Tuple::color(1., 0., 0.));
+        assert_eq!(c.alpha_at(3, 3), 1.);
+        assert_eq!(c.pixel_at(1, 1), Color::BLACK);
+    }
+
+    #[test]
+    fn filling_a_polygon() {
+        let mut c = Canvas::new(6, 6);
+
+        c.fill_polygon(&[(1., 1.), (5., 1.), (1., 5.)], Color::WHITE);
+
+        assert_eq!(c.pixel_at(1, 1), Color::WHITE);
+        assert_eq!(c.pixel_at(1, 2), Color::WHITE);
+        assert_eq!(c.pixel_at(2, 1), Color::WHITE);
+        assert_eq!(c.pixel_at(2, 2), Color::WHITE);
+        assert_abs_diff_eq!(coverage(&c, 4, 1), 6. / 16.);
+        assert_abs_diff_eq!(coverage(&c, 3, 2), 6. / 16.);
+        assert_eq!(c.pixel_at(4, 4), Color::BLACK);
+        assert_eq!(c.pixel_at(0, 0), Color::BLACK);
+    }
+
+    #[test]
+    fn drawing_blends_over_transparent_pixels() {
+        let mut c = Canvas::new(11, 11);
+        for y in 0..11 {
+            for x in 0..11 {
+                c.write_alpha(x, y, 0.);
+            }
+        }
+
+        c.fill_circle((5.5, 5.5), 3., Tuple::color(0., 1., 0.));
+
+        assert_eq!(c.pixel_at(5, 2), Tuple::color(0., 1., 0.));
+        assert_eq!(c.alpha_at(5, 2), 0.5);
+        assert_eq!(c.alpha_at(5, 5), 1.);
+    }
+}
@@ -0,0 +1,199 @@
+use super::Canvas;
+use crate::tuples::Color;
+
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 7;
+const ADVANCE: usize = GLYPH_WIDTH + 1;
+const LINE_HEIGHT: usize = GLYPH_HEIGHT + 1;
+
+/// Rows of a 5x7 glyph from top to bottom, with the leftmost pixel in bit 4. Letters
+/// are upper case only; characters without a glyph are drawn as `?`.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+/// Width and height in pixels of `text` drawn with the given scale.
+pub fn text_size(text: &str, scale: usize) -> (usize, usize) {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let width = (columns * ADVANCE).saturating_sub(1) * scale;
+    let height = (lines.len() * LINE_HEIGHT - 1) * scale;
+    (width, height)
+}
+
+impl Canvas {
+    /// Draws `text` with its top left corner at (`x`, `y`), every font pixel becoming
+    /// a `scale` by `scale` square. Lines are separated by `\n`. Only what falls on the
+    /// canvas is walked, so long text or a large scale costs no more than the pixels
+    /// it covers.
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, color: Color, scale: usize) {
+        for (line_index, line) in text.split('\n').enumerate() {
+            let top = offset(y, line_index, LINE_HEIGHT.saturating_mul(scale));
+            if top >= self.height {
+                break;
+            }
+            for (column, c) in line.chars().enumerate() {
+                let left = offset(x, column, ADVANCE.saturating_mul(scale));
+                if left >= self.width {
+                    break;
+                }
+                for (row, bits) in glyph(c).iter().enumerate() {
+                    let cell_top = offset(top, row, scale);
+                    let bottom = cell_top.saturating_add(scale).min(self.height);
+                    for bit in 0..GLYPH_WIDTH {
+                        if bits & (0x10 >> bit) == 0 {
+                            continue;
+                        }
+                        let cell_left = offset(left, bit, scale);
+                        let right = cell_left.saturating_add(scale).min(self.width);
+                        for py in cell_top..bottom {
+                            for px in cell_left..right {
+                                self.blend_pixel(px as isize, py as isize, color, 1.);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `start` moved on by `index` steps, stopping at `usize::MAX`, which is off any canvas.
+fn offset(start: usize, index: usize, step: usize) -> usize {
+    start.saturating_add(index.saturating_mul(step))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn lit(canvas: &Canvas) -> Vec<(usize, usize)> {
+        let mut result = vec![];
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                if canvas.pixel_at(x, y) != Color::BLACK {
+                    result.push((x, y));
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn drawing_a_character() {
+        let mut c = Canvas::new(7, 9);
+
+        c.draw_text(1, 1, "T", Color::WHITE, 1);
+
+        let pixels = lit(&c);
+        assert_eq!(pixels.len(), 5 + 6);
+        assert!(pixels.contains(&(1, 1)));
+        assert!(pixels.contains(&(5, 1)));
+        assert!(pixels.contains(&(3, 7)));
+        assert!(!pixels.contains(&(1, 2)));
+    }
+
+    #[test]
+    fn lower_case_letters_are_drawn_in_upper_case() {
+        let mut lower = Canvas::new(12, 8);
+        let mut upper = Canvas::new(12, 8);
+
+        lower.draw_text(0, 0, "ab", Color::WHITE, 1);
+        upper.draw_text(0, 0, "AB", Color::WHITE, 1);
+
+        assert_eq!(lit(&lower), lit(&upper));
+    }
+
+    #[test]
+    fn scaling_text() {
+        let mut c = Canvas::new(12, 16);
+
+        c.draw_text(0, 0, "-", Color::WHITE, 2);
+
+        let pixels = lit(&c);
+        assert_eq!(pixels.len(), 5 * 4);
+        assert!(pixels.contains(&(0, 6)));
+        assert!(pixels.contains(&(9, 7)));
+    }
+
+    #[test]
+    fn text_is_clipped_to_the_canvas() {
+        let mut c = Canvas::new(3, 3);
+
+        c.draw_text(1, 1, "88", Color::WHITE, 1);
+
+        assert!(!lit(&c).is_empty());
+    }
+
+    #[test]
+    fn text_far_off_the_canvas_is_skipped() {
+        let mut c = Canvas::new(3, 3);
+        let long = "T".repeat(100_000);
+
+        c.draw_text(usize::MAX - 3, 0, "88", Color::WHITE, 2);
+        c.draw_text(0, usize::MAX, "88", Color::WHITE, usize::MAX);
+        assert!(lit(&c).is_empty());
+
+        c.draw_text(0, 0, &long, Color::WHITE, 1 << 40);
+        assert_eq!(lit(&c).len(), 9);
+    }
+
+    #[test]
+    fn measuring_text() {
+        assert_eq!(text_size("", 1), (0, 7));
+        assert_eq!(text_size("AB", 1), (11, 7));
+        assert_eq!(text_size("AB\nC", 2), (22, 30));
+    }
+}
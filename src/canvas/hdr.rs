@@ -0,0 +1,284 @@
+use super::{image_length, Canvas, ImageError};
+use crate::tuples::{Color, Scalar, Tuple};
+
+impl Canvas {
+    /// Radiance RGBE image with uncompressed scanlines.
+    pub fn to_hdr(&self) -> Vec<u8> {
+        let mut result = format!(
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        )
+        .into_bytes();
+        result.reserve(self.width * self.height * 4);
+        for row in self.rows() {
+            for pixel in row {
+                result.extend_from_slice(&to_rgbe(pixel));
+            }
+        }
+        result
+    }
+
+    /// Reads Radiance images stored top to bottom, with flat or run-length encoded
+    /// scanlines.
+    pub fn from_hdr(data: &[u8]) -> Result<Canvas, ImageError> {
+        let mut lines = data.split(|byte| *byte == b'\n');
+        let magic = lines.next().unwrap_or_default();
+        if !magic.starts_with(b"#?") {
+            return Err(ImageError::InvalidMagicNumber);
+        }
+        let mut position = magic.len() + 1;
+        for line in lines.by_ref() {
+            position += line.len() + 1;
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with(b"FORMAT=") && line != b"FORMAT=32-bit_rle_rgbe" {
+                return Err(ImageError::MalformedHeader(
+                    String::from_utf8_lossy(line).into(),
+                ));
+            }
+        }
+        let resolution = lines.next().ok_or(ImageError::TruncatedData)?;
+        position += resolution.len() + 1;
+        let (width, height) = parse_resolution(resolution)?;
+
+        let mut data = data.get(position..).unwrap_or_default();
+        image_length(width, height, 4)?;
+        if data.len() < height * min_scanline_length(width) {
+            return Err(ImageError::TruncatedData);
+        }
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            let (scanline, rest) = read_scanline(data, width)?;
+            for (x, rgbe) in scanline.chunks_exact(4).enumerate() {
+                canvas.write_pixel(x, y, from_rgbe([rgbe[0], rgbe[1], rgbe[2], rgbe[3]]));
+            }
+            data = rest;
+        }
+        Ok(canvas)
+    }
+}
+
+fn parse_resolution(line: &[u8]) -> Result<(usize, usize), ImageError> {
+    let malformed = || ImageError::MalformedHeader(String::from_utf8_lossy(line).into());
+    let line = std::str::from_utf8(line).map_err(|_| malformed())?;
+    match line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => Ok((
+            width.parse().map_err(|_| malformed())?,
+            height.parse().map_err(|_| malformed())?,
+        )),
+        _ => Err(malformed()),
+    }
+}
+
+fn can_be_run_length_encoded(width: usize) -> bool {
+    (8..0x8000).contains(&width)
+}
+
+/// The fewest bytes a scanline can take: a flat one, or an encoded one made of the
+/// longest runs.
+fn min_scanline_length(width: usize) -> usize {
+    if can_be_run_length_encoded(width) {
+        (4 + 4 * 2 * width.div_ceil(127)).min(width * 4)
+    } else {
+        width * 4
+    }
+}
+
+/// Returns the RGBE bytes of one scanline and the data following it.
+fn read_scanline(data: &[u8], width: usize) -> Result<(Vec<u8>, &[u8]), ImageError> {
+    let run_length_encoded = can_be_run_length_encoded(width)
+        && data.len() >= 4
+        && data[0] == 2
+        && data[1] == 2
+        && ((data[2] as usize) << 8 | data[3] as usize) == width;
+    if !run_length_encoded {
+        let length = width * 4;
+        if data.len() < length {
+            return Err(ImageError::TruncatedData);
+        }
+        return Ok((data[..length].to_vec(), &data[length..]));
+    }
+
+    let mut scanline = vec![0; width * 4];
+    let mut position = 4;
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = *data.get(position).ok_or(ImageError::TruncatedData)? as usize;
+            position += 1;
+            if count > 128 {
+                let value = *data.get(position).ok_or(ImageError::TruncatedData)?;
+                position += 1;
+                for _ in 0..count - 128 {
+                    *scanline
+                        .get_mut(x * 4 + channel)
+                        .ok_or_else(|| ImageError::InvalidSample("run too long".into()))? = value;
+                    x += 1;
+                }
+            } else {
+                for _ in 0..count {
+                    let value = *data.get(position).ok_or(ImageError::TruncatedData)?;
+                    position += 1;
+                    *scanline
+                        .get_mut(x * 4 + channel)
+                        .ok_or_else(|| ImageError::InvalidSample("run too long".into()))? = value;
+                    x += 1;
+                }
+            }
+        }
+    }
+    Ok((scanline, &data[position..]))
+}
+
+fn from_rgbe([red, green, blue, exponent]: [u8; 4]) -> Color {
+    if exponent == 0 {
+        return Tuple::color(0., 0., 0.);
+    }
+    let scale = (2 as Scalar).powi(exponent as i32 - 136);
+    Tuple::color(
+        red as Scalar * scale,
+        green as Scalar * scale,
+        blue as Scalar * scale,
+    )
+}
+
+const MAX_RGBE_EXPONENT: i32 = u8::MAX as i32 - 128;
+
+fn to_rgbe(color: &Color) -> [u8; 4] {
+    let red = color.red().max(0.);
+    let green = color.green().max(0.);
+    let blue = color.blue().max(0.);
+    let max = red.max(green).max(blue);
+    if max < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    // Values beyond what the exponent byte can hold saturate to the largest mantissa.
+    let exponent = (max.log2().floor() + 1.).min(MAX_RGBE_EXPONENT as Scalar) as i32;
+    let scale = 256. / (2 as Scalar).powi(exponent);
+    let mantissa = |value: Scalar| (value * scale).min(255.) as u8;
+    [
+        mantissa(red),
+        mantissa(green),
+        mantissa(blue),
+        (exponent + 128) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tuples::Tuple;
+
+    #[test]
+    fn encoding_colors_as_rgbe() {
+        assert_eq!(to_rgbe(&Tuple::color(0., 0., 0.)), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(&Tuple::color(1., 1., 1.)), [128, 128, 128, 129]);
+        assert_eq!(to_rgbe(&Tuple::color(0.5, 0.25, 0.)), [128, 64, 0, 128]);
+        assert_eq!(to_rgbe(&Tuple::color(12., 3., 1.5)), [192, 48, 24, 132]);
+        assert_eq!(to_rgbe(&Tuple::color(-1., 1., 0.)), [0, 128, 0, 129]);
+    }
+
+    #[test]
+    fn encoding_huge_colors_saturates() {
+        assert_eq!(
+            to_rgbe(&Tuple::color(1e39, 1e39, 1e39)),
+            [255, 255, 255, 255]
+        );
+        assert_eq!(
+            to_rgbe(&Tuple::color(Scalar::INFINITY, 1., 0.)),
+            [255, 0, 0, 255]
+        );
+        assert!(from_rgbe([255, 255, 255, 255]).red() > 1e38);
+    }
+
+    #[test]
+    fn constructing_hdr_header() {
+        let c = Canvas::new(5, 3);
+
+        let hdr = c.to_hdr();
+
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 3 +X 5\n";
+        assert_eq!(&hdr[..header.len()], header);
+        assert_eq!(hdr.len(), header.len() + 5 * 3 * 4);
+    }
+
+    #[test]
+    fn hdr_pixels_are_stored_top_to_bottom() {
+        let mut c = Canvas::new(2, 2);
+        c.write_pixel(1, 0, Tuple::color(4., 2., 1.));
+
+        let hdr = c.to_hdr();
+
+        let pixels = &hdr[hdr.len() - 16..];
+        assert_eq!(&pixels[4..8], &[128, 64, 32, 131]);
+        assert_eq!(&pixels[8..16], &[0; 8]);
+    }
+
+    #[test]
+    fn decoding_rgbe_colors() {
+        assert_eq!(from_rgbe([0, 0, 0, 0]), Tuple::color(0., 0., 0.));
+        assert_eq!(from_rgbe([128, 128, 128, 129]), Tuple::color(1., 1., 1.));
+        assert_eq!(from_rgbe([192, 48, 24, 132]), Tuple::color(12., 3., 1.5));
+    }
+
+    #[test]
+    fn writing_and_reading_back_hdr() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(1, 0, Tuple::color(4., 2., 1.));
+        c.write_pixel(2, 1, Tuple::color(0.5, 0.25, 0.125));
+
+        let canvas = Canvas::from_hdr(&c.to_hdr()).unwrap();
+
+        assert_eq!(canvas.width(), 3);
+        assert_eq!(canvas.height(), 2);
+        assert_eq!(canvas.pixel_at(1, 0), Tuple::color(4., 2., 1.));
+        assert_eq!(canvas.pixel_at(2, 1), Tuple::color(0.5, 0.25, 0.125));
+        assert_eq!(canvas.pixel_at(0, 0), Tuple::color(0., 0., 0.));
+    }
+
+    #[test]
+    fn reading_run_length_encoded_scanlines() {
+        let mut hdr = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 8\n".to_vec();
+        hdr.extend([2, 2, 0, 8]);
+        hdr.extend([136, 128]);
+        hdr.extend([2, 64, 32, 134, 0]);
+        hdr.extend([136, 0]);
+        hdr.extend([136, 129]);
+
+        let canvas = Canvas::from_hdr(&hdr).unwrap();
+
+        assert_eq!(canvas.pixel_at(0, 0), Tuple::color(1., 0.5, 0.));
+        assert_eq!(canvas.pixel_at(1, 0), Tuple::color(1., 0.25, 0.));
+        assert_eq!(canvas.pixel_at(7, 0), Tuple::color(1., 0., 0.));
+    }
+
+    #[test]
+    fn reading_malformed_hdr_data() {
+        assert!(matches!(
+            Canvas::from_hdr(b"P3\n"),
+            Err(ImageError::InvalidMagicNumber)
+        ));
+        assert!(matches!(
+            Canvas::from_hdr(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n"),
+            Err(ImageError::MalformedHeader(_))
+        ));
+        assert!(matches!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n+Y 1 +X 1\n"),
+            Err(ImageError::MalformedHeader(_))
+        ));
+        assert!(matches!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n-Y 1 +X 2\n\x80\x80\x80\x81"),
+            Err(ImageError::TruncatedData)
+        ));
+        assert!(matches!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n-Y 99999999999 +X 99999999999\n\x02\x02"),
+            Err(ImageError::MalformedHeader(_))
+        ));
+        assert!(matches!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n-Y 100000 +X 100000\n\x02\x02\x00\x08"),
+            Err(ImageError::TruncatedData)
+        ));
+    }
+}
@@ -0,0 +1,314 @@
+use super::{Canvas, Rect};
+use crate::tuples::{Color, Scalar};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeFilter {
+    Box,
+    Bilinear,
+}
+
+impl ResizeFilter {
+    /// For every target position along one axis, the source positions it draws from
+    /// with their weights.
+    fn weights(&self, source: usize, target: usize) -> Vec<Vec<(usize, Scalar)>> {
+        let scale = source as Scalar / target as Scalar;
+        (0..target)
+            .map(|i| match self {
+                ResizeFilter::Box => {
+                    let start = i as Scalar * scale;
+                    let end = (i + 1) as Scalar * scale;
+                    (start.floor() as usize..(end.ceil() as usize).min(source))
+                        .map(|j| {
+                            let overlap = end.min((j + 1) as Scalar) - start.max(j as Scalar);
+                            (j, overlap)
+                        })
+                        .filter(|(_, w)| *w > 0.)
+                        .collect()
+                }
+                ResizeFilter::Bilinear => {
+                    let position =
+                        ((i as Scalar + 0.5) * scale - 0.5).clamp(0., (source - 1) as Scalar);
+                    let j = position.floor() as usize;
+                    let f = position - j as Scalar;
+                    vec![(j, 1. - f), ((j + 1).min(source - 1), f)]
+                }
+            })
+            .collect()
+    }
+}
+
+/// Porter-Duff "over" for unpremultiplied colors.
+pub(super) fn over(
+    below: Color,
+    below_alpha: Scalar,
+    color: Color,
+    alpha: Scalar,
+) -> (Color, Scalar) {
+    let result_alpha = alpha + below_alpha * (1. - alpha);
+    if result_alpha <= 0. {
+        return (Color::BLACK, 0.);
+    }
+    let blended = (color * alpha + below * (below_alpha * (1. - alpha))) / result_alpha;
+    (blended, result_alpha)
+}
+
+impl Canvas {
+    /// A blank canvas keeping the output transform of this one.
+    fn with_size_of(&self, width: usize, height: usize) -> Canvas {
+        let mut result = Canvas::new(width, height);
+        result.output_transform = self.output_transform;
+        result
+    }
+
+    pub fn crop(&self, rect: Rect) -> Canvas {
+        let view = self.view(rect);
+        let mut result = self.with_size_of(rect.width, rect.height);
+        for y in 0..rect.height {
+            for x in 0..rect.width {
+                result.write_pixel(x, y, view.pixel_at(x, y));
+                result.write_alpha(x, y, view.alpha_at(x, y));
+            }
+        }
+        result
+    }
+
+    /// Copies `source` with its corner at (`x`, `y`), leaving out whatever falls
+    /// outside of this canvas.
+    pub fn blit(&mut self, source: &Canvas, x: usize, y: usize) {
+        self.draw_canvas(source, x, y, |_, _, color, alpha| (color, alpha));
+    }
+
+    /// Places `source` over this canvas with its corner at (`x`, `y`).
+    pub fn composite(&mut self, source: &Canvas, x: usize, y: usize) {
+        self.draw_canvas(source, x, y, over);
+    }
+
+    fn draw_canvas<F>(&mut self, source: &Canvas, x: usize, y: usize, blend: F)
+    where
+        F: Fn(Color, Scalar, Color, Scalar) -> (Color, Scalar),
+    {
+        let area = self
+            .bounds()
+            .intersection(&Rect::new(x, y, source.width, source.height));
+        for ty in area.y..area.y + area.height {
+            for tx in area.x..area.x + area.width {
+                let (color, alpha) = blend(
+                    self.pixel_at(tx, ty),
+                    self.alpha_at(tx, ty),
+                    source.pixel_at(tx - x, ty - y),
+                    source.alpha_at(tx - x, ty - y),
+                );
+                self.write_pixel(tx, ty, color);
+                self.write_alpha(tx, ty, alpha);
+            }
+        }
+    }
+
+    /// Colors are unpremultiplied, so source pixels are weighted by their alpha.
+    pub fn resize(&self, width: usize, height: usize, filter: ResizeFilter) -> Canvas {
+        let mut result = self.with_size_of(width, height);
+        if self.width == 0 || self.height == 0 {
+            return result;
+        }
+        let columns = filter.weights(self.width, width);
+        let rows = filter.weights(self.height, height);
+        for (y, row_weights) in rows.iter().enumerate() {
+            for (x, column_weights) in columns.iter().enumerate() {
+                let mut color = Color::BLACK;
+                let mut weighted_color = Color::BLACK;
+                let mut weight = 0.;
+                let mut coverage = 0.;
+                for (sy, wy) in row_weights {
+                    for (sx, wx) in column_weights {
+                        let w = wx * wy;
+                        let alpha = self.alpha_at(*sx, *sy);
+                        let c = self.pixel_at(*sx, *sy);
+                        color += c * w;
+                        weighted_color += c * (w * alpha);
+                        weight += w;
+                        coverage += w * alpha;
+                    }
+                }
+                let color = if coverage > 0. {
+                    weighted_color / coverage
+                } else {
+                    color / weight
+                };
+                result.write_pixel(x, y, color);
+                result.write_alpha(x, y, coverage / weight);
+            }
+        }
+        result
+    }
+
+    pub fn flip_horizontal(&self) -> Canvas {
+        let mut result = self.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                result.write_pixel(x, y, self.pixel_at(self.width - 1 - x, y));
+                result.write_alpha(x, y, self.alpha_at(self.width - 1 - x, y));
+            }
+        }
+        result
+    }
+
+    pub fn flip_vertical(&self) -> Canvas {
+        let mut result = self.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                result.write_pixel(x, y, self.pixel_at(x, self.height - 1 - y));
+                result.write_alpha(x, y, self.alpha_at(x, self.height - 1 - y));
+            }
+        }
+        result
+    }
+
+    pub fn map<F>(&self, f: F) -> Canvas
+    where
+        F: Fn(Color) -> Color,
+    {
+        let mut result = self.clone();
+        for pixel in result.pixels.iter_mut() {
+            *pixel = f(*pixel);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::canvas::tests::numbered;
+    use crate::tuples::Tuple;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn cropping_a_canvas() {
+        let mut c = numbered(4, 3);
+        c.write_alpha(2, 1, 0.5);
+
+        let cropped = c.crop(Rect::new(1, 1, 2, 2));
+
+        assert_eq!(cropped.width(), 2);
+        assert_eq!(cropped.height(), 2);
+        assert_eq!(cropped.pixel_at(0, 0), Tuple::color(1., 1., 0.));
+        assert_eq!(cropped.pixel_at(1, 1), Tuple::color(2., 2., 0.));
+        assert_eq!(cropped.alpha_at(1, 0), 0.5);
+    }
+
+    #[test]
+    fn blitting_is_clipped_to_the_canvas() {
+        let mut c = Canvas::new(3, 3);
+        let mut source = Canvas::new(2, 2);
+        for y in 0..2 {
+            for x in 0..2 {
+                source.write_pixel(x, y, Color::WHITE);
+            }
+        }
+        source.write_alpha(0, 0, 0.);
+
+        c.blit(&source, 2, 1);
+
+        assert_eq!(c.pixel_at(2, 1), Color::WHITE);
+        assert_eq!(c.alpha_at(2, 1), 0.);
+        assert_eq!(c.pixel_at(2, 2), Color::WHITE);
+        assert_eq!(c.pixel_at(1, 1), Color::BLACK);
+    }
+
+    #[test]
+    fn compositing_over_an_opaque_canvas() {
+        let mut c = Canvas::new(1, 1);
+        c.write_pixel(0, 0, Tuple::color(0., 0., 1.));
+        let mut source = Canvas::new(1, 1);
+        source.write_pixel(0, 0, Tuple::color(1., 0., 0.));
+        source.write_alpha(0, 0, 0.25);
+
+        c.composite(&source, 0, 0);
+
+        assert_eq!(c.pixel_at(0, 0), Tuple::color(0.25, 0., 0.75));
+        assert_eq!(c.alpha_at(0, 0), 1.);
+    }
+
+    #[test]
+    fn compositing_transparent_canvases() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Tuple::color(0., 0., 1.));
+        c.write_alpha(0, 0, 0.5);
+        c.write_alpha(1, 0, 0.);
+        let mut source = Canvas::new(2, 1);
+        source.write_pixel(0, 0, Tuple::color(1., 0., 0.));
+        source.write_alpha(0, 0, 0.5);
+        source.write_alpha(1, 0, 0.);
+
+        c.composite(&source, 0, 0);
+
+        assert_abs_diff_eq!(c.alpha_at(0, 0), 0.75);
+        assert_abs_diff_eq!(c.pixel_at(0, 0), Tuple::color(2. / 3., 0., 1. / 3.));
+        assert_eq!(c.alpha_at(1, 0), 0.);
+    }
+
+    #[test]
+    fn box_filtering_averages_pixels() {
+        let c = numbered(4, 2);
+
+        let small = c.resize(2, 1, ResizeFilter::Box);
+
+        assert_eq!(small.pixel_at(0, 0), Tuple::color(0.5, 0.5, 0.));
+        assert_eq!(small.pixel_at(1, 0), Tuple::color(2.5, 0.5, 0.));
+    }
+
+    #[test]
+    fn box_filtering_by_a_fractional_factor() {
+        let c = numbered(3, 1);
+
+        let small = c.resize(2, 1, ResizeFilter::Box);
+
+        assert_abs_diff_eq!(small.pixel_at(0, 0).red(), (0. + 0.5) / 1.5);
+        assert_abs_diff_eq!(small.pixel_at(1, 0).red(), (0.5 + 2.) / 1.5);
+    }
+
+    #[test]
+    fn bilinear_filtering_interpolates_pixels() {
+        let c = numbered(2, 1);
+
+        let large = c.resize(4, 2, ResizeFilter::Bilinear);
+
+        for (x, red) in [(0, 0.), (1, 0.25), (2, 0.75), (3, 1.)] {
+            assert_eq!(large.pixel_at(x, 0).red(), red);
+            assert_eq!(large.pixel_at(x, 1).red(), red);
+        }
+    }
+
+    #[test]
+    fn resizing_weights_colors_by_alpha() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Tuple::color(1., 0., 0.));
+        c.write_pixel(1, 0, Tuple::color(0., 1., 0.));
+        c.write_alpha(1, 0, 0.);
+
+        let small = c.resize(1, 1, ResizeFilter::Box);
+
+        assert_eq!(small.pixel_at(0, 0), Tuple::color(1., 0., 0.));
+        assert_eq!(small.alpha_at(0, 0), 0.5);
+    }
+
+    #[test]
+    fn flipping_a_canvas() {
+        let c = numbered(3, 2);
+
+        assert_eq!(c.flip_horizontal().pixel_at(0, 1), Tuple::color(2., 1., 0.));
+        assert_eq!(c.flip_vertical().pixel_at(0, 1), Tuple::color(0., 0., 0.));
+        assert_eq!(c.flip_vertical().pixel_at(2, 0), Tuple::color(2., 1., 0.));
+    }
+
+    #[test]
+    fn mapping_every_pixel() {
+        let c = numbered(3, 2);
+
+        let doubled = c.map(|color| color * 2.);
+
+        assert_eq!(doubled.pixel_at(2, 1), Tuple::color(4., 2., 0.));
+        assert_eq!(doubled.width(), 3);
+    }
+}
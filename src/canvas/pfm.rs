@@ -0,0 +1,142 @@
+use super::{image_length, ppm::PpmReader, Canvas, ImageError};
+use crate::tuples::{Scalar, Tuple};
+
+impl Canvas {
+    /// Portable FloatMap with little-endian 32-bit floats. As the format requires,
+    /// scanlines go from the bottom of the image to the top.
+    pub fn to_pfm(&self) -> Vec<u8> {
+        let mut result = format!("PF\n{} {}\n-1.0\n", self.width, self.height).into_bytes();
+        result.reserve(self.width * self.height * 12);
+        for row in self.rows().rev() {
+            for pixel in row {
+                for value in [pixel.red(), pixel.green(), pixel.blue()] {
+                    result.extend_from_slice(&(value as f32).to_le_bytes());
+                }
+            }
+        }
+        result
+    }
+
+    /// Reads colour (`PF`) and greyscale (`Pf`) PFM images in either byte order.
+    pub fn from_pfm(data: &[u8]) -> Result<Canvas, ImageError> {
+        let mut reader = PpmReader::new(data);
+        let channels = match reader.next_token() {
+            Some(b"PF") => 3,
+            Some(b"Pf") => 1,
+            _ => return Err(ImageError::InvalidMagicNumber),
+        };
+        let width = reader.next_header_value("width")?;
+        let height = reader.next_header_value("height")?;
+        let scale = reader
+            .next_token()
+            .and_then(|token| std::str::from_utf8(token).ok())
+            .and_then(|token| token.parse::<f32>().ok())
+            .filter(|scale| *scale != 0.)
+            .ok_or_else(|| ImageError::MalformedHeader("invalid scale".into()))?;
+
+        let data = reader.binary_data();
+        if data.len() < image_length(width, height, channels * 4)? {
+            return Err(ImageError::TruncatedData);
+        }
+        let samples: Vec<Scalar> = data
+            .chunks_exact(4)
+            .take(width * height * channels)
+            .map(|bytes| {
+                let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+                if scale < 0. {
+                    f32::from_le_bytes(bytes) as Scalar
+                } else {
+                    f32::from_be_bytes(bytes) as Scalar
+                }
+            })
+            .collect();
+
+        let mut canvas = Canvas::new(width, height);
+        for (i, pixel) in samples.chunks_exact(channels).enumerate() {
+            let color = match pixel {
+                [value] => Tuple::color(*value, *value, *value),
+                _ => Tuple::color(pixel[0], pixel[1], pixel[2]),
+            };
+            canvas.write_pixel(i % width, height - 1 - i / width, color);
+        }
+        Ok(canvas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tuples::Tuple;
+
+    #[test]
+    fn constructing_pfm_header() {
+        let c = Canvas::new(5, 3);
+
+        let pfm = c.to_pfm();
+
+        let header = b"PF\n5 3\n-1.0\n";
+        assert_eq!(&pfm[..header.len()], header);
+        assert_eq!(pfm.len(), header.len() + 5 * 3 * 12);
+    }
+
+    #[test]
+    fn pfm_keeps_values_outside_the_displayable_range() {
+        let mut c = Canvas::new(2, 2);
+        c.write_pixel(1, 1, Tuple::color(1.5, -0.25, 1000.));
+
+        let pfm = c.to_pfm();
+
+        let header_length = b"PF\n2 2\n-1.0\n".len();
+        let pixel = &pfm[header_length + 12..header_length + 24];
+        assert_eq!(&pixel[0..4], &1.5_f32.to_le_bytes());
+        assert_eq!(&pixel[4..8], &(-0.25_f32).to_le_bytes());
+        assert_eq!(&pixel[8..12], &1000_f32.to_le_bytes());
+    }
+
+    #[test]
+    fn writing_and_reading_back_pfm() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(0, 0, Tuple::color(1.5, -0.25, 1000.));
+        c.write_pixel(2, 1, Tuple::color(0.5, 0.25, 0.125));
+
+        let canvas = Canvas::from_pfm(&c.to_pfm()).unwrap();
+
+        assert_eq!(canvas.width(), 3);
+        assert_eq!(canvas.height(), 2);
+        assert_eq!(canvas.pixel_at(0, 0), Tuple::color(1.5, -0.25, 1000.));
+        assert_eq!(canvas.pixel_at(2, 1), Tuple::color(0.5, 0.25, 0.125));
+    }
+
+    #[test]
+    fn reading_a_big_endian_greyscale_pfm() {
+        let mut pfm = b"Pf\n1 2\n1.0\n".to_vec();
+        pfm.extend(2_f32.to_be_bytes());
+        pfm.extend(0.5_f32.to_be_bytes());
+
+        let canvas = Canvas::from_pfm(&pfm).unwrap();
+
+        assert_eq!(canvas.pixel_at(0, 0), Tuple::color(0.5, 0.5, 0.5));
+        assert_eq!(canvas.pixel_at(0, 1), Tuple::color(2., 2., 2.));
+    }
+
+    #[test]
+    fn reading_malformed_pfm_data() {
+        assert!(matches!(
+            Canvas::from_pfm(b"P6\n1 1\n-1.0\n"),
+            Err(ImageError::InvalidMagicNumber)
+        ));
+        assert!(matches!(
+            Canvas::from_pfm(b"PF\n1 1\nscale\n"),
+            Err(ImageError::MalformedHeader(_))
+        ));
+        assert!(matches!(
+            Canvas::from_pfm(b"PF\n1 1\n-1.0\n\0\0\0\0"),
+            Err(ImageError::TruncatedData)
+        ));
+        assert!(matches!(
+            Canvas::from_pfm(b"PF\n99999999999 99999999999\n-1.0\n\0\0\0\0"),
+            Err(ImageError::MalformedHeader(_))
+        ));
+    }
+}
@@ -0,0 +1,311 @@
+use super::{image_length, Canvas, ImageError};
+use crate::tuples::{Scalar, Tuple};
+
+pub(super) struct PpmReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PpmReader<'a> {
+    pub(super) fn new(data: &'a [u8]) -> PpmReader<'a> {
+        PpmReader { data, position: 0 }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&byte) = self.data.get(self.position) {
+            if byte == b'#' {
+                while self.position < self.data.len() && self.data[self.position] != b'\n' {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub(super) fn next_token(&mut self) -> Option<&'a [u8]> {
+        self.skip_whitespace_and_comments();
+        let start = self.position;
+        while self.position < self.data.len() && !self.data[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+        if start == self.position {
+            None
+        } else {
+            Some(&self.data[start..self.position])
+        }
+    }
+
+    pub(super) fn next_header_value(&mut self, name: &str) -> Result<usize, ImageError> {
+        let token = self
+            .next_token()
+            .ok_or_else(|| ImageError::MalformedHeader(format!("missing {name}")))?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| {
+                ImageError::MalformedHeader(format!(
+                    "invalid {name}: {}",
+                    String::from_utf8_lossy(token)
+                ))
+            })
+    }
+
+    fn next_sample(&mut self) -> Result<usize, ImageError> {
+        let token = self.next_token().ok_or(ImageError::TruncatedData)?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| ImageError::InvalidSample(String::from_utf8_lossy(token).into()))
+    }
+
+    pub(super) fn binary_data(&mut self) -> &'a [u8] {
+        &self.data[(self.position + 1).min(self.data.len())..]
+    }
+}
+
+impl Canvas {
+    /// Reads plain (P3) and binary (P6) PPM images, scaling samples by the maximum
+    /// value given in the header.
+    pub fn from_ppm(data: &[u8]) -> Result<Canvas, ImageError> {
+        let mut reader = PpmReader::new(data);
+        let binary = match reader.next_token() {
+            Some(b"P3") => false,
+            Some(b"P6") => true,
+            _ => return Err(ImageError::InvalidMagicNumber),
+        };
+        let width = reader.next_header_value("width")?;
+        let height = reader.next_header_value("height")?;
+        let max_value = reader.next_header_value("maximum value")?;
+        if max_value == 0 || max_value > u16::MAX as usize {
+            return Err(ImageError::MalformedHeader(format!(
+                "maximum value out of range: {max_value}"
+            )));
+        }
+
+        let length = image_length(width, height, 3)?;
+        let data = reader.binary_data();
+        let mut samples = Vec::new();
+        if binary {
+            let bytes_per_sample = if max_value < 256 { 1 } else { 2 };
+            if data.len() < image_length(width, height, 3 * bytes_per_sample)? {
+                return Err(ImageError::TruncatedData);
+            }
+            samples.reserve(length);
+            for sample in data.chunks_exact(bytes_per_sample).take(length) {
+                samples.push(match sample {
+                    [value] => *value as usize,
+                    [high, low] => u16::from_be_bytes([*high, *low]) as usize,
+                    _ => unreachable!(),
+                });
+            }
+        } else {
+            // Every plain sample takes at least one byte.
+            if data.len() < length {
+                return Err(ImageError::TruncatedData);
+            }
+            samples.reserve(length);
+            for _ in 0..length {
+                samples.push(reader.next_sample()?);
+            }
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        let scale = max_value as Scalar;
+        for (i, rgb) in samples.chunks_exact(3).enumerate() {
+            if let Some(value) = rgb.iter().find(|value| **value > max_value) {
+                return Err(ImageError::SampleOutOfRange(*value, max_value));
+            }
+            canvas.write_pixel(
+                i % width,
+                i / width,
+                Tuple::color(
+                    rgb[0] as Scalar / scale,
+                    rgb[1] as Scalar / scale,
+                    rgb[2] as Scalar / scale,
+                ),
+            );
+        }
+        Ok(canvas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn reading_a_file_with_the_wrong_magic_number() {
+        let ppm = b"P32\n1 1\n255\n0 0 0\n";
+
+        let result = Canvas::from_ppm(ppm);
+
+        assert!(matches!(result, Err(ImageError::InvalidMagicNumber)));
+    }
+
+    #[test]
+    fn reading_a_ppm_returns_a_canvas_of_the_right_size() {
+        let ppm = b"P3\n10 2\n255\n\
+            0 0 0  0 0 0  0 0 0  0 0 0  0 0 0\n\
+            0 0 0  0 0 0  0 0 0  0 0 0  0 0 0\n\
+            0 0 0  0 0 0  0 0 0  0 0 0  0 0 0\n\
+            0 0 0  0 0 0  0 0 0  0 0 0  0 0 0\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(canvas.width(), 10);
+        assert_eq!(canvas.height(), 2);
+    }
+
+    #[test]
+    fn reading_pixel_data_from_a_ppm_file() {
+        let ppm = b"P3\n4 3\n255\n\
+            255 127 0  0 127 255  127 255 0  255 255 255\n\
+            0 0 0  255 0 0  0 255 0  0 0 255\n\
+            255 255 0  0 255 255  255 0 255  127 127 127\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        for (x, y, color) in [
+            (0, 0, Tuple::color(1., 0.49804, 0.)),
+            (1, 0, Tuple::color(0., 0.49804, 1.)),
+            (2, 0, Tuple::color(0.49804, 1., 0.)),
+            (3, 0, Tuple::color(1., 1., 1.)),
+            (0, 1, Tuple::color(0., 0., 0.)),
+            (1, 1, Tuple::color(1., 0., 0.)),
+            (2, 1, Tuple::color(0., 1., 0.)),
+            (3, 1, Tuple::color(0., 0., 1.)),
+            (0, 2, Tuple::color(1., 1., 0.)),
+            (1, 2, Tuple::color(0., 1., 1.)),
+            (2, 2, Tuple::color(1., 0., 1.)),
+            (3, 2, Tuple::color(0.49804, 0.49804, 0.49804)),
+        ] {
+            approx::assert_abs_diff_eq!(canvas.pixel_at(x, y), color, epsilon = 0.00001);
+        }
+    }
+
+    #[test]
+    fn ppm_parsing_ignores_comment_lines() {
+        let ppm = b"P3\n# this is a comment\n2 1\n# this, too\n255\n# another comment\n\
+            255 255 255\n# oh, no, comments in the pixel data!\n255 0 255\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(canvas.pixel_at(0, 0), Tuple::color(1., 1., 1.));
+        assert_eq!(canvas.pixel_at(1, 0), Tuple::color(1., 0., 1.));
+    }
+
+    #[test]
+    fn ppm_parsing_allows_an_rgb_triple_to_span_lines() {
+        let ppm = b"P3\n1 1\n255\n51\n153\n\n204\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(canvas.pixel_at(0, 0), Tuple::color(0.2, 0.6, 0.8));
+    }
+
+    #[test]
+    fn ppm_parsing_respects_the_scale_setting() {
+        let ppm = b"P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n";
+
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!(canvas.pixel_at(0, 1), Tuple::color(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn reading_a_binary_ppm() {
+        let mut ppm = b"P6\n# comment\n2 1\n255\n".to_vec();
+        ppm.extend([255, 0, 51, 0, 255, 0]);
+
+        let canvas = Canvas::from_ppm(&ppm).unwrap();
+
+        assert_eq!(canvas.pixel_at(0, 0), Tuple::color(1., 0., 0.2));
+        assert_eq!(canvas.pixel_at(1, 0), Tuple::color(0., 1., 0.));
+    }
+
+    #[test]
+    fn reading_a_binary_ppm_with_two_byte_samples() {
+        let mut ppm = b"P6 1 1 65535\n".to_vec();
+        ppm.extend([0xff, 0xff, 0x00, 0x00, 0x80, 0x00]);
+
+        let canvas = Canvas::from_ppm(&ppm).unwrap();
+
+        approx::assert_abs_diff_eq!(
+            canvas.pixel_at(0, 0),
+            Tuple::color(1., 0., 0.5),
+            epsilon = 0.0001
+        );
+    }
+
+    #[test]
+    fn writing_and_reading_back_binary_ppm() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(2, 1, Tuple::color(1., 0.2, 0.6));
+
+        let canvas = Canvas::from_ppm(&c.to_ppm_binary()).unwrap();
+
+        assert_eq!(canvas.pixel_at(2, 1), Tuple::color(1., 0.2, 0.6));
+    }
+
+    #[test]
+    fn reading_a_ppm_with_missing_header_values() {
+        let result = Canvas::from_ppm(b"P3\n2\n");
+
+        assert!(matches!(result, Err(ImageError::MalformedHeader(_))));
+    }
+
+    #[test]
+    fn reading_a_ppm_with_an_invalid_maximum_value() {
+        assert!(matches!(
+            Canvas::from_ppm(b"P3\n1 1\n0\n0 0 0\n"),
+            Err(ImageError::MalformedHeader(_))
+        ));
+        assert!(matches!(
+            Canvas::from_ppm(b"P3\n1 1\nabc\n0 0 0\n"),
+            Err(ImageError::MalformedHeader(_))
+        ));
+    }
+
+    #[test]
+    fn reading_a_ppm_with_too_little_pixel_data() {
+        assert!(matches!(
+            Canvas::from_ppm(b"P3\n2 1\n255\n0 0 0\n"),
+            Err(ImageError::TruncatedData)
+        ));
+        assert!(matches!(
+            Canvas::from_ppm(b"P6\n2 1\n255\n\x00\x00\x00"),
+            Err(ImageError::TruncatedData)
+        ));
+    }
+
+    #[test]
+    fn reading_a_ppm_with_impossible_dimensions() {
+        for ppm in [
+            &b"P3\n99999999999 99999999999\n255\n0 0 0\n"[..],
+            b"P6\n99999999999 99999999999\n255\n\x00\x00\x00",
+        ] {
+            assert!(matches!(
+                Canvas::from_ppm(ppm),
+                Err(ImageError::MalformedHeader(_))
+            ));
+        }
+        assert!(matches!(
+            Canvas::from_ppm(b"P3\n100000 100000\n255\n0 0 0\n"),
+            Err(ImageError::TruncatedData)
+        ));
+    }
+
+    #[test]
+    fn reading_a_ppm_with_samples_above_the_maximum() {
+        let result = Canvas::from_ppm(b"P3\n1 1\n100\n101 0 0\n");
+
+        assert!(matches!(
+            result,
+            Err(ImageError::SampleOutOfRange(101, 100))
+        ));
+    }
+}
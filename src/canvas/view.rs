@@ -0,0 +1,174 @@
+use super::Canvas;
+use crate::tuples::{Color, Scalar};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        Rect::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+
+    pub fn area(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+/// Read-only access to a rectangle of a canvas, addressed relative to its corner.
+pub struct View<'a> {
+    canvas: &'a Canvas,
+    rect: Rect,
+}
+
+impl<'a> View<'a> {
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn width(&self) -> usize {
+        self.rect.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rect.height
+    }
+
+    pub fn pixel_at(&self, x: usize, y: usize) -> Color {
+        assert!(x < self.rect.width && y < self.rect.height);
+        self.canvas.pixel_at(self.rect.x + x, self.rect.y + y)
+    }
+
+    pub fn alpha_at(&self, x: usize, y: usize) -> Scalar {
+        assert!(x < self.rect.width && y < self.rect.height);
+        self.canvas.alpha_at(self.rect.x + x, self.rect.y + y)
+    }
+
+    pub fn row(&self, y: usize) -> &'a [Color] {
+        assert!(y < self.rect.height);
+        &self.canvas.row(self.rect.y + y)[self.rect.x..self.rect.x + self.rect.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [Color]> + '_ {
+        (0..self.rect.height).map(|y| self.row(y))
+    }
+}
+
+impl Canvas {
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    pub fn view(&self, rect: Rect) -> View<'_> {
+        assert_eq!(
+            self.bounds().intersection(&rect),
+            rect,
+            "View {rect:?} is outside of the canvas"
+        );
+        View { canvas: self, rect }
+    }
+
+    /// Splits the canvas into tiles of at most `size` by `size` pixels, row by row.
+    pub fn tiles(&self, size: usize) -> Vec<Rect> {
+        let size = size.max(1);
+        let mut result = vec![];
+        for y in (0..self.height).step_by(size) {
+            for x in (0..self.width).step_by(size) {
+                result.push(Rect::new(
+                    x,
+                    y,
+                    size.min(self.width - x),
+                    size.min(self.height - y),
+                ));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::canvas::tests::numbered;
+    use crate::tuples::Tuple;
+
+    #[test]
+    fn intersecting_rectangles() {
+        let a = Rect::new(0, 0, 4, 3);
+
+        assert_eq!(
+            a.intersection(&Rect::new(2, 1, 5, 5)),
+            Rect::new(2, 1, 2, 2)
+        );
+        assert_eq!(a.intersection(&Rect::new(5, 0, 1, 1)).area(), 0);
+        assert!(a.contains(3, 2));
+        assert!(!a.contains(4, 2));
+    }
+
+    #[test]
+    fn accessing_rows_of_a_canvas() {
+        let mut c = numbered(3, 2);
+
+        assert_eq!(c.row(1)[2], Tuple::color(2., 1., 0.));
+        assert_eq!(c.rows().count(), 2);
+        assert_eq!(c.pixels().len(), 6);
+
+        c.row_mut(0)[1] = Color::WHITE;
+        assert_eq!(c.pixel_at(1, 0), Color::WHITE);
+    }
+
+    #[test]
+    fn a_view_is_addressed_relative_to_its_corner() {
+        let c = numbered(5, 4);
+
+        let v = c.view(Rect::new(1, 2, 3, 2));
+
+        assert_eq!(v.width(), 3);
+        assert_eq!(v.height(), 2);
+        assert_eq!(v.pixel_at(0, 0), Tuple::color(1., 2., 0.));
+        assert_eq!(v.row(1), &c.row(3)[1..4]);
+        assert_eq!(v.rows().count(), 2);
+        assert_eq!(v.alpha_at(2, 1), 1.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn a_view_must_lie_within_the_canvas() {
+        numbered(5, 4).view(Rect::new(3, 0, 3, 1));
+    }
+
+    #[test]
+    fn splitting_a_canvas_into_tiles() {
+        let c = Canvas::new(5, 3);
+
+        let tiles = c.tiles(2);
+
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[0], Rect::new(0, 0, 2, 2));
+        assert_eq!(tiles[2], Rect::new(4, 0, 1, 2));
+        assert_eq!(tiles[5], Rect::new(4, 2, 1, 1));
+        assert_eq!(tiles.iter().map(Rect::area).sum::<usize>(), 15);
+    }
+}
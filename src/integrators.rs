@@ -0,0 +1,325 @@
+use crate::{
+    intersections::{hit, Computations},
+    rays::Ray,
+    sampling::{cosine_hemisphere, Sampler},
+    tuples::{Color, Scalar, Tuple},
+    world::World,
+};
+use std::fmt::Debug;
+
+pub use crate::world::AmbientOcclusion;
+
+pub trait Integrator: Debug {
+    fn radiance(&self, world: &World, ray: &Ray, sampler: &mut dyn Sampler) -> Color;
+}
+
+/// Direct lighting from the point lights with the materials' ambient term, as computed
+/// by `World::color_at`.
+#[derive(Debug, Clone, Copy)]
+pub struct Whitted;
+
+impl Integrator for Whitted {
+    fn radiance(&self, world: &World, ray: &Ray, sampler: &mut dyn Sampler) -> Color {
+        world.color_at_sampled(ray, sampler)
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn radiance(&self, world: &World, ray: &Ray, sampler: &mut dyn Sampler) -> Color {
+        let intersections = world.intersect_visible(ray);
+        match hit(&intersections) {
+            None => Color::WHITE,
+            Some(h) => {
+                let comps = Computations::prepare(h, ray);
+                let a = self.accessibility(world, &comps, sampler);
+                Tuple::color(a, a, a)
+            }
+        }
+    }
+}
+
+/// Unidirectional path tracing with next-event estimation towards the point lights.
+/// Indirect light replaces the materials' ambient term, which is ignored. Paths that
+/// escape pick up the background only when it is enabled as a light source.
+#[derive(Debug, Clone, Copy)]
+pub struct PathTracer {
+    pub max_depth: usize,
+    pub russian_roulette_depth: usize,
+}
+
+const MIN_SURVIVAL: Scalar = 0.05;
+
+impl PathTracer {
+    pub fn new(max_depth: usize) -> PathTracer {
+        PathTracer {
+            max_depth,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        PathTracer {
+            max_depth: 8,
+            russian_roulette_depth: 3,
+        }
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(&self, world: &World, ray: &Ray, sampler: &mut dyn Sampler) -> Color {
+        let mut radiance = Color::BLACK;
+        let mut throughput = Color::WHITE;
+        let mut ray = Ray::new(ray.origin, ray.direction).with_time(ray.time);
+
+        for depth in 0..self.max_depth {
+            let intersections = if depth == 0 {
+                world.intersect_visible(&ray)
+            } else {
+                world.intersect(&ray)
+            };
+            let Some(h) = hit(&intersections) else {
+                if depth == 0 || world.background_lighting() {
+                    radiance += throughput * world.background_at(&ray.direction);
+                }
+                break;
+            };
+            let comps = Computations::prepare(h, &ray);
+            let material = comps.object.material();
+            let color =
+                material
+                    .pattern
+                    .pattern_at_shape_time(comps.object, &comps.over_point, comps.time);
+
+            radiance += throughput * material.emissive;
+            for light in world.lights() {
+                let lightv = (light.position - comps.over_point).normalize();
+                let reflected = material.reflectance(&color, &lightv, &comps.eyev, &comps.normalv);
+                if reflected != Color::BLACK {
+                    let transmittance = world.shadow_transmittance(&comps, light);
+                    radiance += throughput * reflected * light.intensity * transmittance;
+                }
+            }
+
+            let direction = cosine_hemisphere(sampler.next_2d(), &comps.normalv);
+            let cos_theta = direction.dot(&comps.normalv);
+            if cos_theta <= 0. {
+                break;
+            }
+            throughput = throughput
+                * material.reflectance(&color, &direction, &comps.eyev, &comps.normalv)
+                / cos_theta;
+
+            if depth + 1 >= self.russian_roulette_depth {
+                let survival = throughput
+                    .red()
+                    .max(throughput.green())
+                    .max(throughput.blue())
+                    .clamp(MIN_SURVIVAL, 1.);
+                if sampler.next_1d() >= survival {
+                    break;
+                }
+                throughput = throughput / survival;
+            }
+
+            ray = Ray::new(comps.over_point, direction).with_time(comps.time);
+        }
+
+        radiance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{
+        backgrounds::Constant, lights::PointLight, sampling::RandomSampler, shapes::Shape,
+        transformations::Transformation, tuples::Tuple,
+    };
+    use approx::assert_abs_diff_eq;
+
+    fn single_sphere_world() -> World {
+        let light = PointLight::new(Tuple::point(-10., 10., -10.), Tuple::color(1., 1., 1.));
+        let mut s = Shape::sphere();
+        s.material_mut().set_color(Tuple::color(0.8, 1., 0.6));
+        s.material_mut().ambient = 0.;
+        World::with_objects_and_light(vec![s], light)
+    }
+
+    #[test]
+    fn whitted_integrator_matches_color_at() {
+        let w = World::default();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = Whitted.radiance(&w, &r, &mut sampler);
+
+        assert_eq!(c, w.color_at(&r));
+    }
+
+    #[test]
+    fn a_path_that_misses_everything_is_black() {
+        let w = World::default();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 1., 0.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = PathTracer::default().radiance(&w, &r, &mut sampler);
+
+        assert_eq!(c, Color::BLACK);
+    }
+
+    #[test]
+    fn a_lone_convex_object_receives_only_direct_light() {
+        let w = single_sphere_world();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = PathTracer::default().radiance(&w, &r, &mut sampler);
+
+        assert_abs_diff_eq!(c, w.color_at(&r), epsilon = 0.00001);
+    }
+
+    #[test]
+    fn emission_is_seen_directly() {
+        let mut s = Shape::sphere();
+        s.material_mut().emissive = Tuple::color(1., 0.5, 0.25);
+        let w = World::with_objects_and_light(
+            vec![s],
+            PointLight::new(Tuple::point(0., 0., -10.), Color::BLACK),
+        );
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = PathTracer::new(1).radiance(&w, &r, &mut sampler);
+
+        assert_eq!(c, Tuple::color(1., 0.5, 0.25));
+    }
+
+    #[test]
+    fn light_bounces_off_nearby_surfaces() {
+        let light = PointLight::new(Tuple::point(0., 10., 0.), Tuple::color(1., 1., 1.));
+        let mut floor = Shape::plane();
+        floor.material_mut().specular = 0.;
+        let mut wall = Shape::sphere();
+        wall.set_transform(Transformation::translation(2., 1., 0.));
+        wall.material_mut().set_color(Tuple::color(1., 0., 0.));
+        wall.material_mut().specular = 0.;
+        let w = World::with_objects_and_light(vec![floor, wall], light);
+        let r = Ray::new(Tuple::point(0.5, 1., 0.), Tuple::vector(0., -1., 0.));
+        let tracer = PathTracer::default();
+        let mut sampler = RandomSampler::new(3);
+
+        let mut c = Color::BLACK;
+        for _ in 0..256 {
+            c += tracer.radiance(&w, &r, &mut sampler) / 256.;
+        }
+
+        assert!(c.green() > 0.85);
+        assert!(c.red() > c.green() + 0.05);
+        assert_abs_diff_eq!(c.green(), c.blue(), epsilon = 0.00001);
+    }
+
+    #[test]
+    fn an_open_surface_is_not_occluded() {
+        let w = World::with_objects_and_light(
+            vec![Shape::plane()],
+            PointLight::new(Tuple::point(0., 10., 0.), Color::WHITE),
+        );
+        let r = Ray::new(Tuple::point(0., 1., 0.), Tuple::vector(0., -1., 0.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = AmbientOcclusion::new(32, 10.).radiance(&w, &r, &mut sampler);
+
+        assert_eq!(c, Color::WHITE);
+    }
+
+    #[test]
+    fn an_enclosed_surface_is_fully_occluded() {
+        let w = World::default();
+        let r = Ray::new(Tuple::point(0., 0., 0.75), Tuple::vector(0., 0., -1.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = AmbientOcclusion::new(32, 10.).radiance(&w, &r, &mut sampler);
+
+        assert_eq!(c, Color::BLACK);
+    }
+
+    #[test]
+    fn a_surface_next_to_an_object_is_partially_occluded() {
+        let mut ball = Shape::sphere();
+        ball.set_transform(Transformation::translation(0., 1., 0.));
+        let w = World::with_objects_and_light(
+            vec![Shape::plane(), ball],
+            PointLight::new(Tuple::point(0., 10., 0.), Color::WHITE),
+        );
+        let r = Ray::new(Tuple::point(1., 1., 0.), Tuple::vector(0., -1., 0.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = AmbientOcclusion::new(256, 10.).radiance(&w, &r, &mut sampler);
+
+        assert!(c.red() > 0.2 && c.red() < 0.9);
+        assert_eq!(c.red(), c.green());
+        assert_eq!(c.red(), c.blue());
+    }
+
+    #[test]
+    fn occlusion_is_limited_to_the_maximum_distance() {
+        let w = World::default();
+        let r = Ray::new(Tuple::point(0., 0., 0.75), Tuple::vector(0., 0., -1.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = AmbientOcclusion::new(32, 0.1).radiance(&w, &r, &mut sampler);
+
+        assert_eq!(c, Color::WHITE);
+    }
+
+    #[test]
+    fn a_ray_that_misses_is_unoccluded() {
+        let w = World::default();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 1., 0.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = AmbientOcclusion::new(32, 10.).radiance(&w, &r, &mut sampler);
+
+        assert_eq!(c, Color::WHITE);
+    }
+
+    fn sky_lit_floor() -> World {
+        let mut floor = Shape::plane();
+        floor.material_mut().specular = 0.;
+        let mut w = World::with_objects_and_light(
+            vec![floor],
+            PointLight::new(Tuple::point(0., 10., 0.), Color::BLACK),
+        );
+        w.set_background(Box::new(Constant::new(Color::WHITE)));
+        w
+    }
+
+    #[test]
+    fn a_path_that_misses_everything_sees_the_background() {
+        let w = sky_lit_floor();
+        let r = Ray::new(Tuple::point(0., 1., 0.), Tuple::vector(0., 1., 0.));
+        let mut sampler = RandomSampler::new(0);
+
+        let c = PathTracer::default().radiance(&w, &r, &mut sampler);
+
+        assert_eq!(c, Color::WHITE);
+    }
+
+    #[test]
+    fn the_background_lights_surfaces_only_when_enabled() {
+        let mut w = sky_lit_floor();
+        let r = Ray::new(Tuple::point(0., 1., 0.), Tuple::vector(0., -1., 0.));
+        let mut sampler = RandomSampler::new(0);
+
+        let unlit = PathTracer::default().radiance(&w, &r, &mut sampler);
+        w.set_background_lighting(true);
+        let lit = PathTracer::default().radiance(&w, &r, &mut sampler);
+
+        assert_eq!(unlit, Color::BLACK);
+        assert_abs_diff_eq!(lit, Tuple::color(0.9, 0.9, 0.9), epsilon = 1e-12);
+        assert_abs_diff_eq!(lit, Whitted.radiance(&w, &r, &mut sampler), epsilon = 1e-12);
+    }
+}
@@ -12,7 +12,7 @@ pub struct Intersection<'a> {
 }
 
 impl Intersection<'_> {
-    pub fn new(t: Scalar, object: &Shape) -> Intersection {
+    pub fn new(t: Scalar, object: &Shape) -> Intersection<'_> {
         Intersection { t, object }
     }
 }
@@ -39,13 +39,14 @@ pub fn hit<'a>(intersections: &'a [Intersection<'a>]) -> Option<&'a Intersection
 }
 
 pub struct Computations<'a> {
-    t: Scalar,
+    pub t: Scalar,
     pub object: &'a Shape,
     pub point: Point,
     pub eyev: Vector,
     pub normalv: Vector,
-    inside: bool,
+    pub inside: bool,
     pub over_point: Point,
+    pub time: Scalar,
 }
 
 const EPSILON: Scalar = 0.00001;
@@ -53,7 +54,7 @@ const EPSILON: Scalar = 0.00001;
 impl Computations<'_> {
     pub fn prepare<'a>(intersection: &Intersection<'a>, ray: &Ray) -> Computations<'a> {
         let point = ray.position(intersection.t);
-        let mut normalv = intersection.object.normal_at(&point);
+        let mut normalv = intersection.object.normal_at_time(&point, ray.time);
         let eyev = -ray.direction;
         let inside: bool;
         if normalv.dot(&eyev) < 0. {
@@ -70,6 +71,7 @@ impl Computations<'_> {
             normalv,
             inside,
             over_point: point + normalv * EPSILON,
+            time: ray.time,
         }
     }
 }
@@ -205,4 +207,20 @@ mod tests {
         assert!(comps.over_point.z < -EPSILON / 2.);
         assert!(comps.point.z > comps.over_point.z);
     }
+
+    #[test]
+    fn precomputing_an_intersection_with_a_moving_shape() {
+        let r = Ray::new(Tuple::point(2., 0., -5.), Tuple::vector(0., 0., 1.)).with_time(1.);
+        let mut shape = Shape::sphere();
+        shape.set_motion(
+            Transformation::IDENTITY,
+            Transformation::translation(2., 0., 0.),
+        );
+        let i = Intersection::new(4., &shape);
+
+        let comps = Computations::prepare(&i, &r);
+
+        assert_eq!(comps.time, 1.);
+        assert_eq!(comps.normalv, Tuple::vector(0., 0., -1.));
+    }
 }
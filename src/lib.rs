@@ -1,12 +1,18 @@
+pub mod animation;
+pub mod backgrounds;
 pub mod camera;
 pub mod canvas;
+pub mod integrators;
 pub mod intersections;
 pub mod lights;
 pub mod materials;
 pub mod matrices;
+pub mod passes;
 pub mod patterns;
 pub mod rays;
+pub mod sampling;
 pub mod shapes;
+pub mod tone_mapping;
 pub mod transformations;
 pub mod tuples;
 pub mod world;
@@ -4,16 +4,33 @@ use crate::{
     shapes::Shape,
     tuples::{Color, Point, Scalar, Tuple, Vector},
 };
+use std::f64::consts::PI;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    Phong,
+    /// GGX distribution with Cook-Torrance specular. `diffuse` and `specular` scale
+    /// the two lobes; at 1 the surface reflects no more light than it receives.
+    Microfacet,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Material {
     pub pattern: Pattern,
     pub ambient: Scalar,
     pub diffuse: Scalar,
     pub specular: Scalar,
     pub shininess: Scalar,
+    pub model: Model,
+    pub metallic: Scalar,
+    pub roughness: Scalar,
+    pub emissive: Color,
+    pub transparency: Scalar,
 }
 
+const DIELECTRIC_REFLECTANCE: Scalar = 0.04;
+const MIN_ALPHA: Scalar = 0.001;
+
 impl Material {
     pub fn set_color(&mut self, color: Color) {
         self.pattern = Pattern::solid(color);
@@ -26,35 +43,144 @@ impl Material {
         point: &Point,
         eyev: &Vector,
         normalv: &Vector,
-        in_shadow: bool,
+        transmittance: &Color,
     ) -> Color {
         let color = self.pattern.pattern_at_shape(object, point);
-        let effective_color = color * light.intensity;
+        self.lighting_of(&color, light, point, eyev, normalv, transmittance, 1.)
+    }
+
+    /// Lighting of a surface whose `color` has been looked up already, with the
+    /// ambient term scaled by the `accessibility` of the point to ambient light.
+    #[allow(clippy::too_many_arguments)]
+    pub fn lighting_of(
+        &self,
+        color: &Color,
+        light: &PointLight,
+        point: &Point,
+        eyev: &Vector,
+        normalv: &Vector,
+        transmittance: &Color,
+        accessibility: Scalar,
+    ) -> Color {
         let lightv = (light.position - *point).normalize();
-        let ambient = effective_color * self.ambient;
-        let light_dot_normal = lightv.dot(normalv);
+        let ambient = self.ambient_of(color, light) * accessibility;
 
-        let diffuse: Color;
-        let specular: Color;
-        if in_shadow || light_dot_normal < 0. {
-            diffuse = Color::BLACK;
-            specular = Color::BLACK;
+        if *transmittance == Color::BLACK || lightv.dot(normalv) < 0. {
+            ambient
         } else {
-            diffuse = effective_color * self.diffuse * light_dot_normal;
-            let reflectv = (-lightv).reflect(normalv);
-            let reflect_dot_eye = reflectv.dot(eyev);
-            if reflect_dot_eye < 0. {
-                specular = Color::BLACK;
-            } else {
-                let factor = reflect_dot_eye.powf(self.shininess);
-                specular = light.intensity * self.specular * factor;
-            }
-        }
-
-        ambient + diffuse + specular
+            let arriving = light.intensity * *transmittance;
+            let (diffuse, specular) = self.reflected(color, &lightv, eyev, normalv);
+            ambient + diffuse * arriving + specular * arriving
+        }
+    }
+
+    fn ambient_of(&self, color: &Color, light: &PointLight) -> Color {
+        *color * light.intensity * self.ambient
+    }
+
+    /// Light reflected towards `eyev` for a unit intensity light in direction `lightv`,
+    /// cosine factor included. Both models agree that a white diffuse surface facing
+    /// the light reflects the light's full intensity, so lights need no rescaling when
+    /// switching models.
+    pub fn reflectance(
+        &self,
+        color: &Color,
+        lightv: &Vector,
+        eyev: &Vector,
+        normalv: &Vector,
+    ) -> Color {
+        let (diffuse, specular) = self.reflected(color, lightv, eyev, normalv);
+        diffuse + specular
+    }
+
+    fn reflected(
+        &self,
+        color: &Color,
+        lightv: &Vector,
+        eyev: &Vector,
+        normalv: &Vector,
+    ) -> (Color, Color) {
+        match self.model {
+            Model::Phong => self.phong(color, lightv, eyev, normalv),
+            Model::Microfacet => self.microfacet(color, lightv, eyev, normalv),
+        }
+    }
+
+    fn phong(
+        &self,
+        color: &Color,
+        lightv: &Vector,
+        eyev: &Vector,
+        normalv: &Vector,
+    ) -> (Color, Color) {
+        let light_dot_normal = lightv.dot(normalv);
+        if light_dot_normal < 0. {
+            return (Color::BLACK, Color::BLACK);
+        }
+        let diffuse = *color * self.diffuse * light_dot_normal;
+        let reflectv = (-*lightv).reflect(normalv);
+        let reflect_dot_eye = reflectv.dot(eyev);
+        if reflect_dot_eye < 0. {
+            (diffuse, Color::BLACK)
+        } else {
+            let factor = reflect_dot_eye.powf(self.shininess);
+            (diffuse, Color::WHITE * self.specular * factor)
+        }
+    }
+
+    fn microfacet(
+        &self,
+        color: &Color,
+        lightv: &Vector,
+        eyev: &Vector,
+        normalv: &Vector,
+    ) -> (Color, Color) {
+        let n_dot_l = normalv.dot(lightv);
+        let n_dot_v = normalv.dot(eyev);
+        if n_dot_l <= 0. || n_dot_v <= 0. {
+            return (Color::BLACK, Color::BLACK);
+        }
+        let halfv = (*lightv + *eyev).normalize();
+        let n_dot_h = normalv.dot(&halfv).max(0.);
+        let v_dot_h = eyev.dot(&halfv).max(0.);
+
+        let alpha = (self.roughness * self.roughness).max(MIN_ALPHA);
+        let f0 = Tuple::color(
+            DIELECTRIC_REFLECTANCE,
+            DIELECTRIC_REFLECTANCE,
+            DIELECTRIC_REFLECTANCE,
+        ) * (1. - self.metallic)
+            + *color * self.metallic;
+
+        let fresnel = fresnel_schlick(v_dot_h, &f0);
+        let distribution = ggx_distribution(n_dot_h, alpha);
+        let geometry = smith_geometry(n_dot_v, n_dot_l, self.roughness);
+
+        let specular =
+            fresnel * (self.specular * distribution * geometry / (4. * n_dot_v * n_dot_l));
+        let diffuse_weight = (Color::WHITE - fresnel) * (1. - self.metallic);
+        let diffuse = diffuse_weight * *color * (self.diffuse / PI);
+
+        (diffuse * (n_dot_l * PI), specular * (n_dot_l * PI))
     }
 }
 
+fn ggx_distribution(n_dot_h: Scalar, alpha: Scalar) -> Scalar {
+    let alpha2 = alpha * alpha;
+    let denominator = n_dot_h * n_dot_h * (alpha2 - 1.) + 1.;
+    alpha2 / (PI * denominator * denominator)
+}
+
+fn smith_geometry(n_dot_v: Scalar, n_dot_l: Scalar, roughness: Scalar) -> Scalar {
+    let k = (roughness + 1.) * (roughness + 1.) / 8.;
+    let schlick_ggx = |n_dot_x: Scalar| n_dot_x / (n_dot_x * (1. - k) + k);
+    schlick_ggx(n_dot_v) * schlick_ggx(n_dot_l)
+}
+
+fn fresnel_schlick(cos_theta: Scalar, f0: &Color) -> Color {
+    *f0 + (Color::WHITE - *f0) * (1. - cos_theta).powi(5)
+}
+
 impl Default for Material {
     fn default() -> Self {
         Self {
@@ -63,6 +189,11 @@ impl Default for Material {
             diffuse: 0.9,
             specular: 0.9,
             shininess: 200.,
+            model: Model::Phong,
+            metallic: 0.,
+            roughness: 0.5,
+            emissive: Color::BLACK,
+            transparency: 0.,
         }
     }
 }
@@ -84,6 +215,11 @@ mod tests {
         assert_eq!(m.diffuse, 0.9);
         assert_eq!(m.specular, 0.9);
         assert_eq!(m.shininess, 200.);
+        assert_eq!(m.model, Model::Phong);
+        assert_eq!(m.metallic, 0.);
+        assert_eq!(m.roughness, 0.5);
+        assert_eq!(m.emissive, Color::BLACK);
+        assert_eq!(m.transparency, 0.);
     }
 
     mod lighting_tests {
@@ -110,7 +246,7 @@ mod tests {
                 &POSITION,
                 &eyev,
                 &normalv,
-                false,
+                &Color::WHITE,
             );
             assert_eq!(result, Tuple::color(1.9, 1.9, 1.9));
         }
@@ -127,7 +263,7 @@ mod tests {
                 &POSITION,
                 &eyev,
                 &normalv,
-                false,
+                &Color::WHITE,
             );
             assert_eq!(result, Tuple::color(1., 1., 1.));
         }
@@ -144,7 +280,7 @@ mod tests {
                 &POSITION,
                 &eyev,
                 &normalv,
-                false,
+                &Color::WHITE,
             );
             assert_abs_diff_eq!(
                 result,
@@ -165,7 +301,7 @@ mod tests {
                 &POSITION,
                 &eyev,
                 &normalv,
-                false,
+                &Color::WHITE,
             );
             assert_abs_diff_eq!(
                 result,
@@ -186,7 +322,7 @@ mod tests {
                 &POSITION,
                 &eyev,
                 &normalv,
-                false,
+                &Color::WHITE,
             );
             assert_eq!(result, Tuple::color(0.1, 0.1, 0.1));
         }
@@ -196,7 +332,7 @@ mod tests {
             let eyev = Tuple::vector(0., 0., -1.);
             let normalv = Tuple::vector(0., 0., -1.);
             let light = PointLight::new(Tuple::point(0., 0., -10.), Tuple::color(1., 1., 1.));
-            let in_shadow = true;
+            let transmittance = Color::BLACK;
 
             let result = Material::default().lighting(
                 &Shape::sphere(),
@@ -204,12 +340,49 @@ mod tests {
                 &POSITION,
                 &eyev,
                 &normalv,
-                in_shadow,
+                &transmittance,
             );
             assert_eq!(result, Tuple::color(0.1, 0.1, 0.1));
         }
 
         #[test]
+        fn ligthing_with_the_light_partially_transmitted() {
+            let eyev = Tuple::vector(0., 0., -1.);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = PointLight::new(Tuple::point(0., 0., -10.), Tuple::color(1., 1., 1.));
+            let transmittance = Tuple::color(0.5, 0.25, 0.);
+
+            let result = Material::default().lighting(
+                &Shape::sphere(),
+                &light,
+                &POSITION,
+                &eyev,
+                &normalv,
+                &transmittance,
+            );
+            assert_abs_diff_eq!(result, Tuple::color(1., 0.55, 0.1), epsilon = 0.00001);
+        }
+
+        #[test]
+        fn accessibility_scales_only_the_ambient_term() {
+            let eyev = Tuple::vector(0., 0., -1.);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = PointLight::new(Tuple::point(0., 0., -10.), Tuple::color(1., 1., 1.));
+
+            let result = Material::default().lighting_of(
+                &Color::WHITE,
+                &light,
+                &POSITION,
+                &eyev,
+                &normalv,
+                &Color::WHITE,
+                0.5,
+            );
+            assert_abs_diff_eq!(result, Tuple::color(1.85, 1.85, 1.85), epsilon = 0.00001);
+        }
+
+        #[test]
+        #[allow(clippy::field_reassign_with_default)]
         fn lighting_with_a_pattern_applied() {
             let mut m = Material::default();
             m.pattern = Pattern::stripe(Tuple::color(1., 1., 1.), Tuple::color(0., 0., 0.));
@@ -226,7 +399,7 @@ mod tests {
                 &Tuple::point(0.9, 0., 0.),
                 &eyev,
                 &normalv,
-                false,
+                &Color::WHITE,
             );
             let c2 = m.lighting(
                 &Shape::sphere(),
@@ -234,11 +407,193 @@ mod tests {
                 &Tuple::point(1.1, 0., 0.),
                 &eyev,
                 &normalv,
-                false,
+                &Color::WHITE,
             );
 
             assert_eq!(c1, Tuple::color(1., 1., 1.));
             assert_eq!(c2, Tuple::color(0., 0., 0.));
         }
     }
+
+    mod microfacet_tests {
+
+        use super::*;
+        use crate::tuples::Point;
+        use approx::assert_abs_diff_eq;
+        use std::f64::consts::*;
+
+        const POSITION: Point = Tuple::point(0., 0., 0.);
+
+        fn microfacet(color: Color, metallic: Scalar, roughness: Scalar) -> Material {
+            let mut m = Material {
+                ambient: 0.,
+                diffuse: 1.,
+                specular: 1.,
+                model: Model::Microfacet,
+                metallic,
+                roughness,
+                ..Default::default()
+            };
+            m.set_color(color);
+            m
+        }
+
+        fn light_at(position: Point) -> PointLight {
+            PointLight::new(position, Tuple::color(1., 1., 1.))
+        }
+
+        #[test]
+        fn lighting_a_rough_dielectric_head_on() {
+            let m = microfacet(Tuple::color(1., 1., 1.), 0., 1.);
+            let eyev = Tuple::vector(0., 0., -1.);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = light_at(Tuple::point(0., 0., -10.));
+
+            let result = m.lighting(
+                &Shape::sphere(),
+                &light,
+                &POSITION,
+                &eyev,
+                &normalv,
+                &Color::WHITE,
+            );
+
+            assert_abs_diff_eq!(result, Tuple::color(0.97, 0.97, 0.97), epsilon = 0.00001);
+        }
+
+        #[test]
+        fn lighting_a_smoother_dielectric_head_on_has_a_stronger_highlight() {
+            let m = microfacet(Tuple::color(1., 1., 1.), 0., 0.5);
+            let eyev = Tuple::vector(0., 0., -1.);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = light_at(Tuple::point(0., 0., -10.));
+
+            let result = m.lighting(
+                &Shape::sphere(),
+                &light,
+                &POSITION,
+                &eyev,
+                &normalv,
+                &Color::WHITE,
+            );
+
+            assert_abs_diff_eq!(result, Tuple::color(1.12, 1.12, 1.12), epsilon = 0.00001);
+        }
+
+        #[test]
+        fn a_metal_has_no_diffuse_and_tints_its_reflection() {
+            let m = microfacet(Tuple::color(1., 0., 0.), 1., 0.5);
+            let eyev = Tuple::vector(0., 0., -1.);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = light_at(Tuple::point(0., 0., -10.));
+
+            let result = m.lighting(
+                &Shape::sphere(),
+                &light,
+                &POSITION,
+                &eyev,
+                &normalv,
+                &Color::WHITE,
+            );
+
+            assert!(result.red() > 0.);
+            assert_eq!(result.green(), 0.);
+            assert_eq!(result.blue(), 0.);
+        }
+
+        #[test]
+        fn a_smooth_surface_is_dark_away_from_the_mirror_direction() {
+            let m = microfacet(Tuple::color(0., 0., 0.), 0., 0.1);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = light_at(Tuple::point(0., 10., -10.));
+
+            let mirror = m.lighting(
+                &Shape::sphere(),
+                &light,
+                &POSITION,
+                &Tuple::vector(0., -SQRT_2 / 2., -SQRT_2 / 2.),
+                &normalv,
+                &Color::WHITE,
+            );
+            let off_mirror = m.lighting(
+                &Shape::sphere(),
+                &light,
+                &POSITION,
+                &Tuple::vector(0., 0., -1.),
+                &normalv,
+                &Color::WHITE,
+            );
+
+            assert!(mirror.red() > 1.);
+            assert!(off_mirror.red() < 0.001);
+        }
+
+        #[test]
+        fn diffuse_and_specular_scale_the_two_lobes() {
+            let eyev = Tuple::vector(0., 0., -1.);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = light_at(Tuple::point(0., 0., -10.));
+            let lit = |diffuse: Scalar, specular: Scalar| {
+                let mut m = microfacet(Tuple::color(1., 1., 1.), 0., 1.);
+                m.diffuse = diffuse;
+                m.specular = specular;
+                m.lighting(
+                    &Shape::sphere(),
+                    &light,
+                    &POSITION,
+                    &eyev,
+                    &normalv,
+                    &Color::WHITE,
+                )
+            };
+
+            let diffuse_only = lit(1., 0.);
+            let specular_only = lit(0., 1.);
+
+            assert_abs_diff_eq!(diffuse_only + specular_only, lit(1., 1.));
+            assert_abs_diff_eq!(lit(0.5, 0.), diffuse_only * 0.5);
+            assert_abs_diff_eq!(lit(0., 0.5), specular_only * 0.5);
+            assert_eq!(lit(0., 0.), Color::BLACK);
+        }
+
+        #[test]
+        fn lighting_with_the_light_behind_the_surface() {
+            let mut m = microfacet(Tuple::color(1., 1., 1.), 0., 0.5);
+            m.ambient = 0.1;
+            let eyev = Tuple::vector(0., 0., -1.);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = light_at(Tuple::point(0., 0., 10.));
+
+            let result = m.lighting(
+                &Shape::sphere(),
+                &light,
+                &POSITION,
+                &eyev,
+                &normalv,
+                &Color::WHITE,
+            );
+
+            assert_eq!(result, Tuple::color(0.1, 0.1, 0.1));
+        }
+
+        #[test]
+        fn lighting_with_the_surface_in_shadow() {
+            let mut m = microfacet(Tuple::color(1., 1., 1.), 0., 0.5);
+            m.ambient = 0.1;
+            let eyev = Tuple::vector(0., 0., -1.);
+            let normalv = Tuple::vector(0., 0., -1.);
+            let light = light_at(Tuple::point(0., 0., -10.));
+
+            let result = m.lighting(
+                &Shape::sphere(),
+                &light,
+                &POSITION,
+                &eyev,
+                &normalv,
+                &Color::BLACK,
+            );
+
+            assert_eq!(result, Tuple::color(0.1, 0.1, 0.1));
+        }
+    }
 }
@@ -37,6 +37,17 @@ impl Matrix4 {
         1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
     );
 
+    /// Element-wise linear interpolation towards `other`.
+    pub fn lerp(&self, other: &Matrix4, t: Scalar) -> Matrix4 {
+        let mut result = [[0.0; 4]; 4];
+        for (i, row) in result.iter_mut().enumerate() {
+            for (j, item) in row.iter_mut().enumerate() {
+                *item = self.0[i][j] + (other.0[i][j] - self.0[i][j]) * t;
+            }
+        }
+        Matrix4(result)
+    }
+
     pub fn transpose(&self) -> Matrix4 {
         let mut result = [[0.0; 4]; 4];
         for (i, row) in result.iter_mut().enumerate() {
@@ -62,7 +73,7 @@ impl Matrix4 {
     }
 
     fn cofactor(&self, l: usize, k: usize) -> Scalar {
-        (if (l + k) % 2 == 0 { 1.0 } else { -1.0 }) * self.minor(l, k)
+        (if (l + k).is_multiple_of(2) { 1.0 } else { -1.0 }) * self.minor(l, k)
     }
 
     fn determinant(&self) -> Scalar {
@@ -212,7 +223,7 @@ impl Matrix3 {
     }
 
     fn cofactor(&self, l: usize, k: usize) -> Scalar {
-        (if (l + k) % 2 == 0 { 1.0 } else { -1.0 }) * self.minor(l, k)
+        (if (l + k).is_multiple_of(2) { 1.0 } else { -1.0 }) * self.minor(l, k)
     }
 
     pub fn determinant(&self) -> Scalar {
@@ -497,4 +508,19 @@ mod tests {
 
         assert_abs_diff_eq!(c * b.inverse(), a, epsilon = 0.000001);
     }
+
+    #[test]
+    fn interpolating_between_matrices() {
+        let a = Matrix4::IDENTITY;
+        let b = Matrix4::new(
+            3., 0., 0., 4., 0., 3., 0., -2., 0., 0., 3., 0., 0., 0., 0., 1.,
+        );
+
+        assert_eq!(a.lerp(&b, 0.), a);
+        assert_eq!(a.lerp(&b, 1.), b);
+        assert_eq!(
+            a.lerp(&b, 0.5),
+            Matrix4::new(2., 0., 0., 2., 0., 2., 0., -1., 0., 0., 2., 0., 0., 0., 0., 1.)
+        );
+    }
 }
@@ -0,0 +1,93 @@
+use crate::{
+    canvas::Canvas,
+    tuples::{Color, Scalar, Tuple},
+};
+
+/// Auxiliary outputs collected from the first visible hit through each pixel centre.
+/// Pixels whose ray misses are left black. Ids are stored as `index + 1` in every
+/// channel so that zero stands for the background. `SampleCount` holds the number of
+/// camera samples taken for every pixel instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pass {
+    Depth,
+    Normal,
+    Albedo,
+    ObjectId,
+    MaterialId,
+    HitT,
+    SampleCount,
+}
+
+pub struct Passes {
+    pub beauty: Canvas,
+    auxiliary: Vec<(Pass, Canvas)>,
+}
+
+impl Passes {
+    pub fn new(beauty: Canvas, auxiliary: Vec<(Pass, Canvas)>) -> Passes {
+        Passes { beauty, auxiliary }
+    }
+
+    pub fn get(&self, pass: Pass) -> Option<&Canvas> {
+        self.auxiliary
+            .iter()
+            .find(|(p, _)| *p == pass)
+            .map(|(_, canvas)| canvas)
+    }
+
+    pub fn matte(&self, pass: Pass, index: usize) -> Option<Canvas> {
+        let ids = self.get(pass)?;
+        let id = id_value(index);
+        let mut matte = Canvas::new(ids.width(), ids.height());
+        for y in 0..ids.height() {
+            for x in 0..ids.width() {
+                if ids.pixel_at(x, y).red() == id {
+                    matte.write_pixel(x, y, Color::WHITE);
+                }
+            }
+        }
+        Some(matte)
+    }
+}
+
+pub fn id_value(index: usize) -> Scalar {
+    (index + 1) as Scalar
+}
+
+pub fn id_color(index: usize) -> Color {
+    let id = id_value(index);
+    Tuple::color(id, id, id)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn looking_up_a_pass() {
+        let mut depth = Canvas::new(2, 1);
+        depth.write_pixel(1, 0, Tuple::color(3., 3., 3.));
+        let passes = Passes::new(Canvas::new(2, 1), vec![(Pass::Depth, depth)]);
+
+        assert_eq!(
+            passes.get(Pass::Depth).unwrap().pixel_at(1, 0),
+            Tuple::color(3., 3., 3.)
+        );
+        assert!(passes.get(Pass::Normal).is_none());
+    }
+
+    #[test]
+    fn extracting_a_matte_from_an_id_pass() {
+        let mut ids = Canvas::new(3, 1);
+        ids.write_pixel(0, 0, id_color(0));
+        ids.write_pixel(1, 0, id_color(1));
+        let passes = Passes::new(Canvas::new(3, 1), vec![(Pass::ObjectId, ids)]);
+
+        let matte = passes.matte(Pass::ObjectId, 1).unwrap();
+
+        assert_eq!(matte.pixel_at(0, 0), Color::BLACK);
+        assert_eq!(matte.pixel_at(1, 0), Color::WHITE);
+        assert_eq!(matte.pixel_at(2, 0), Color::BLACK);
+    }
+}
@@ -1,16 +1,40 @@
-use std::{fmt::Debug, rc::Rc};
+use std::{any::Any, fmt::Debug, rc::Rc};
 
 use crate::{
     shapes::Shape,
     transformations::Transformation,
-    tuples::{Color, Point},
+    tuples::{Color, Point, Scalar},
 };
 
-pub trait PatternType: Debug {
+pub trait PatternType: Debug + PatternEq {
     fn pattern_at(&self, point: &Point) -> Color;
 }
 
-#[derive(Debug, Clone)]
+/// Structural equality between pattern types behind `dyn PatternType`, implemented
+/// for every pattern type that is `PartialEq`.
+pub trait PatternEq {
+    fn as_any(&self) -> &dyn Any;
+
+    fn eq_pattern(&self, other: &dyn PatternType) -> bool;
+}
+
+impl<T: PatternType + PartialEq + 'static> PatternEq for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_pattern(&self, other: &dyn PatternType) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
+
+impl PartialEq for dyn PatternType {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_pattern(other)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Solid {
     a: Color,
 }
@@ -43,6 +67,12 @@ impl Stripe {
     }
 }
 
+impl PartialEq for Stripe {
+    fn eq(&self, other: &Self) -> bool {
+        *self.a == *other.a && *self.b == *other.b
+    }
+}
+
 impl PatternType for Stripe {
     fn pattern_at(&self, point: &Point) -> Color {
         if point.x.floor() % 2. == 0. {
@@ -69,6 +99,12 @@ impl Gradient {
     }
 }
 
+impl PartialEq for Gradient {
+    fn eq(&self, other: &Self) -> bool {
+        *self.a == *other.a && *self.b == *other.b
+    }
+}
+
 impl PatternType for Gradient {
     fn pattern_at(&self, point: &Point) -> Color {
         let a = self.a.pattern_at(point);
@@ -93,6 +129,12 @@ impl Ring {
     }
 }
 
+impl PartialEq for Ring {
+    fn eq(&self, other: &Self) -> bool {
+        *self.a == *other.a && *self.b == *other.b
+    }
+}
+
 impl PatternType for Ring {
     fn pattern_at(&self, point: &Point) -> Color {
         if (point.x * point.x + point.z * point.z).sqrt() % 2. == 0. {
@@ -119,6 +161,12 @@ impl Checker {
     }
 }
 
+impl PartialEq for Checker {
+    fn eq(&self, other: &Self) -> bool {
+        *self.a == *other.a && *self.b == *other.b
+    }
+}
+
 impl PatternType for Checker {
     fn pattern_at(&self, point: &Point) -> Color {
         if (point.x.floor() + point.y.floor() + point.z.floor()) % 2. == 0. {
@@ -175,13 +223,29 @@ impl Pattern {
     }
 
     pub fn pattern_at_shape(&self, object: &Shape, world_point: &Point) -> Color {
-        let object_point = *object.inversed_transform() * *world_point;
+        self.pattern_at_shape_time(object, world_point, 0.)
+    }
+
+    /// Looks the pattern up where a moving shape is at `time`, so that it moves along.
+    pub fn pattern_at_shape_time(
+        &self,
+        object: &Shape,
+        world_point: &Point,
+        time: Scalar,
+    ) -> Color {
+        let object_point = object.inversed_transform_at(time) * *world_point;
         let pattern_point = self.invered_transform * object_point;
 
         self.pattern_type.pattern_at(&pattern_point)
     }
 }
 
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.transform == other.transform && *self.pattern_type == *other.pattern_type
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -244,6 +308,33 @@ mod tests {
         assert_eq!(c, WHITE);
     }
 
+    #[test]
+    fn pattern_moves_with_a_moving_object() {
+        let mut object = Shape::sphere();
+        object.set_motion(
+            Transformation::IDENTITY,
+            Transformation::translation(1., 0., 0.),
+        );
+        let pattern = Pattern::stripe(WHITE, BLACK);
+        let point = Tuple::point(1.5, 0., 0.);
+
+        assert_eq!(pattern.pattern_at_shape_time(&object, &point, 0.), BLACK);
+        assert_eq!(pattern.pattern_at_shape_time(&object, &point, 1.), WHITE);
+    }
+
+    #[test]
+    fn patterns_are_compared_by_structure() {
+        let pattern = Pattern::stripe(WHITE, BLACK);
+        let mut moved = pattern.clone();
+        moved.set_transform(Transformation::translation(1., 0., 0.));
+
+        assert_eq!(pattern, pattern.clone());
+        assert_eq!(pattern, Pattern::stripe(WHITE, BLACK));
+        assert_ne!(pattern, moved);
+        assert_ne!(pattern, Pattern::stripe(BLACK, WHITE));
+        assert_ne!(pattern, Pattern::checker(WHITE, BLACK));
+    }
+
     mod solid {
 
         use super::*;
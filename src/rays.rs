@@ -3,14 +3,25 @@ use crate::{
     tuples::{Point, Scalar, Vector},
 };
 
+/// `time` runs over the interval in which moving shapes go from their start to their
+/// end transform, from 0 to 1.
 pub struct Ray {
     pub origin: Point,
     pub direction: Vector,
+    pub time: Scalar,
 }
 
 impl Ray {
     pub fn new(origin: Point, direction: Vector) -> Ray {
-        Ray { origin, direction }
+        Ray {
+            origin,
+            direction,
+            time: 0.,
+        }
+    }
+
+    pub fn with_time(self, time: Scalar) -> Ray {
+        Ray { time, ..self }
     }
 
     pub fn position(&self, t: Scalar) -> Point {
@@ -18,7 +29,7 @@ impl Ray {
     }
 
     pub fn transform(&self, m: &Transformation) -> Ray {
-        Ray::new(*m * self.origin, *m * self.direction)
+        Ray::new(*m * self.origin, *m * self.direction).with_time(self.time)
     }
 }
 
@@ -37,6 +48,7 @@ mod tests {
 
         assert_eq!(r.origin, origin);
         assert_eq!(r.direction, direction);
+        assert_eq!(r.time, 0.);
     }
 
     #[test]
@@ -70,4 +82,13 @@ mod tests {
         assert_eq!(r2.origin, Tuple::point(2., 6., 12.));
         assert_eq!(r2.direction, Tuple::vector(0., 3., 0.));
     }
+
+    #[test]
+    fn transforming_a_ray_keeps_its_time() {
+        let r = Ray::new(Tuple::point(1., 2., 3.), Tuple::vector(0., 1., 0.)).with_time(0.25);
+
+        let r2 = r.transform(&Transformation::translation(3., 4., 5.));
+
+        assert_eq!(r2.time, 0.25);
+    }
 }
@@ -0,0 +1,275 @@
+use crate::tuples::{Scalar, Tuple, Vector};
+use std::{f64::consts::PI, fmt::Debug};
+
+mod halton;
+mod sobol;
+mod stratified;
+
+pub use halton::HaltonSampler;
+pub use sobol::SobolSampler;
+pub use stratified::StratifiedSampler;
+
+/// A source of sample values in [0, 1). Renderers call `start_pixel_sample` before
+/// taking each camera sample, after which the values depend only on the sampler's
+/// seed, the pixel, the sample index and how many values were taken before. Pixels
+/// therefore come out the same whatever order they are rendered in.
+pub trait Sampler: Debug {
+    fn next_1d(&mut self) -> Scalar;
+
+    fn next_2d(&mut self) -> (Scalar, Scalar) {
+        (self.next_1d(), self.next_1d())
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), index: usize);
+
+    fn clone_box(&self) -> Box<dyn Sampler>;
+}
+
+/// Uniform random numbers from a SplitMix64 generator, so that a given seed always
+/// produces the same sequence on every platform.
+#[derive(Debug, Clone)]
+pub struct RandomSampler {
+    seed: u64,
+    state: u64,
+}
+
+impl RandomSampler {
+    pub fn new(seed: u64) -> RandomSampler {
+        RandomSampler { seed, state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl Sampler for RandomSampler {
+    fn next_1d(&mut self) -> Scalar {
+        to_unit(self.next_u64())
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), index: usize) {
+        self.state = hash(&[self.seed, pixel.0 as u64, pixel.1 as u64, index as u64]);
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Mixes a list of values into one well distributed 64 bit value.
+fn hash(values: &[u64]) -> u64 {
+    let mut generator = RandomSampler::new(0);
+    for value in values {
+        generator.state ^= *value;
+        generator.state = generator.next_u64();
+    }
+    generator.state
+}
+
+fn to_unit(value: u64) -> Scalar {
+    (value >> 11) as Scalar * (1. / (1u64 << 53) as Scalar)
+}
+
+pub fn orthonormal_basis(normal: &Vector) -> (Vector, Vector) {
+    let sign = 1_f64.copysign(normal.z);
+    let a = -1. / (sign + normal.z);
+    let b = normal.x * normal.y * a;
+    (
+        Tuple::vector(
+            1. + sign * normal.x * normal.x * a,
+            sign * b,
+            -sign * normal.x,
+        ),
+        Tuple::vector(b, sign + normal.y * normal.y * a, -normal.y),
+    )
+}
+
+pub fn cosine_hemisphere((u, v): (Scalar, Scalar), normal: &Vector) -> Vector {
+    let r = u.sqrt();
+    let phi = 2. * PI * v;
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + *normal * (1. - u).max(0.).sqrt()
+}
+
+pub fn uniform_sphere((u, v): (Scalar, Scalar)) -> Vector {
+    let z = 1. - 2. * u;
+    let r = (1. - z * z).max(0.).sqrt();
+    let phi = 2. * PI * v;
+    Tuple::vector(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Discrete distribution proportional to a list of non-negative weights.
+#[derive(Debug, Clone)]
+pub struct Distribution1D {
+    cdf: Vec<Scalar>,
+}
+
+impl Distribution1D {
+    pub fn new(weights: &[Scalar]) -> Option<Distribution1D> {
+        let total: Scalar = weights.iter().map(|w| w.max(0.)).sum();
+        if total <= 0. || !total.is_finite() {
+            return None;
+        }
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        let mut sum = 0.;
+        cdf.push(0.);
+        for weight in weights {
+            sum += weight.max(0.);
+            cdf.push(sum / total);
+        }
+        *cdf.last_mut().unwrap() = 1.;
+        Some(Distribution1D { cdf })
+    }
+
+    pub fn len(&self) -> usize {
+        self.cdf.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn probability(&self, index: usize) -> Scalar {
+        self.cdf[index + 1] - self.cdf[index]
+    }
+
+    /// Returns the chosen index and where `u` fell within its share of the interval.
+    pub fn sample(&self, u: Scalar) -> (usize, Scalar) {
+        let index = self.cdf[1..]
+            .partition_point(|c| *c <= u)
+            .min(self.len() - 1);
+        let offset = (u - self.cdf[index]) / self.probability(index);
+        (index, offset.clamp(0., 1.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn random_numbers_are_in_the_unit_interval() {
+        let mut sampler = RandomSampler::new(7);
+
+        for _ in 0..1000 {
+            let u = sampler.next_1d();
+            assert!((0. ..1.).contains(&u));
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_sequence() {
+        let mut a = RandomSampler::new(42);
+        let mut b = RandomSampler::new(42);
+        let mut c = RandomSampler::new(43);
+
+        let xs: Vec<Scalar> = (0..10).map(|_| a.next_1d()).collect();
+        let ys: Vec<Scalar> = (0..10).map(|_| b.next_1d()).collect();
+        let zs: Vec<Scalar> = (0..10).map(|_| c.next_1d()).collect();
+
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+    }
+
+    #[test]
+    fn a_pixel_sample_does_not_depend_on_earlier_values() {
+        let mut a = RandomSampler::new(5);
+        let mut b = RandomSampler::new(5);
+        for _ in 0..17 {
+            b.next_1d();
+        }
+
+        a.start_pixel_sample((3, 4), 2);
+        b.start_pixel_sample((3, 4), 2);
+
+        assert_eq!(a.next_2d(), b.next_2d());
+        a.start_pixel_sample((4, 3), 2);
+        b.start_pixel_sample((3, 4), 3);
+        assert_ne!(a.next_1d(), b.next_1d());
+    }
+
+    #[test]
+    fn the_seed_changes_pixel_samples() {
+        let mut a = RandomSampler::new(1);
+        let mut b = RandomSampler::new(2);
+
+        a.start_pixel_sample((0, 0), 0);
+        b.start_pixel_sample((0, 0), 0);
+
+        assert_ne!(a.next_1d(), b.next_1d());
+    }
+
+    #[test]
+    fn an_orthonormal_basis_is_perpendicular_to_the_normal() {
+        for normal in [
+            Tuple::vector(0., 0., 1.),
+            Tuple::vector(0., 0., -1.),
+            Tuple::vector(1., 2., 3.).normalize(),
+        ] {
+            let (t, b) = orthonormal_basis(&normal);
+
+            assert_abs_diff_eq!(t.dot(&normal), 0., epsilon = 1e-12);
+            assert_abs_diff_eq!(b.dot(&normal), 0., epsilon = 1e-12);
+            assert_abs_diff_eq!(t.dot(&b), 0., epsilon = 1e-12);
+            assert_abs_diff_eq!(t.magnitude(), 1., epsilon = 1e-12);
+            assert_abs_diff_eq!(b.magnitude(), 1., epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn cosine_weighted_directions_lie_in_the_hemisphere_of_the_normal() {
+        let mut sampler = RandomSampler::new(1);
+        let normal = Tuple::vector(0., 1., 0.);
+        let mut mean_cosine = 0.;
+
+        for _ in 0..10000 {
+            let d = cosine_hemisphere(sampler.next_2d(), &normal);
+            assert_abs_diff_eq!(d.magnitude(), 1., epsilon = 1e-9);
+            assert!(d.dot(&normal) >= 0.);
+            mean_cosine += d.dot(&normal) / 10000.;
+        }
+
+        assert_abs_diff_eq!(mean_cosine, 2. / 3., epsilon = 0.01);
+    }
+
+    #[test]
+    fn uniform_directions_cover_the_sphere() {
+        let mut sampler = RandomSampler::new(5);
+        let mut mean = Tuple::vector(0., 0., 0.);
+
+        for _ in 0..10000 {
+            let d = uniform_sphere(sampler.next_2d());
+            assert_abs_diff_eq!(d.magnitude(), 1., epsilon = 1e-9);
+            mean += d / 10000.;
+        }
+
+        assert_abs_diff_eq!(mean, Tuple::vector(0., 0., 0.), epsilon = 0.03);
+    }
+
+    #[test]
+    fn a_distribution_needs_a_positive_weight() {
+        assert!(Distribution1D::new(&[]).is_none());
+        assert!(Distribution1D::new(&[0., 0.]).is_none());
+    }
+
+    #[test]
+    fn sampling_a_discrete_distribution() {
+        let d = Distribution1D::new(&[1., 0., 3.]).unwrap();
+
+        assert_eq!(d.len(), 3);
+        assert_eq!(d.probability(0), 0.25);
+        assert_eq!(d.probability(1), 0.);
+        assert_eq!(d.probability(2), 0.75);
+        assert_eq!(d.sample(0.), (0, 0.));
+        assert_eq!(d.sample(0.125), (0, 0.5));
+        assert_eq!(d.sample(0.25), (2, 0.));
+        assert_eq!(d.sample(0.625), (2, 0.5));
+    }
+}
@@ -0,0 +1,120 @@
+use super::{hash, to_unit, RandomSampler, Sampler};
+use crate::tuples::Scalar;
+
+const PRIMES: [u64; 16] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+
+/// The Halton sequence with a base per dimension, indexed by the sample number within
+/// a pixel and shifted by a random offset per pixel and dimension so that
+/// neighbouring pixels are not correlated. Dimensions past the last base fall back to
+/// random values.
+#[derive(Debug, Clone)]
+pub struct HaltonSampler {
+    seed: u64,
+    pixel: (usize, usize),
+    index: usize,
+    dimension: usize,
+    random: RandomSampler,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> HaltonSampler {
+        HaltonSampler {
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+            random: RandomSampler::new(seed),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn next_1d(&mut self) -> Scalar {
+        let dimension = self.dimension;
+        self.dimension += 1;
+        if dimension >= PRIMES.len() {
+            return self.random.next_1d();
+        }
+        let value = radical_inverse(PRIMES[dimension], self.index as u64);
+        let shift = to_unit(hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            dimension as u64,
+        ]));
+        let shifted = value + shift;
+        (shifted - shifted.floor()).min(1. - Scalar::EPSILON)
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), index: usize) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+        self.random.start_pixel_sample(pixel, index);
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Mirrors the digits of `index` in `base` around the radix point.
+fn radical_inverse(base: u64, mut index: u64) -> Scalar {
+    let inverse = 1. / base as Scalar;
+    let mut factor = inverse;
+    let mut result = 0.;
+    while index > 0 {
+        result += (index % base) as Scalar * factor;
+        index /= base;
+        factor *= inverse;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn the_radical_inverse_mirrors_digits() {
+        assert_eq!(radical_inverse(2, 0), 0.);
+        assert_eq!(radical_inverse(2, 1), 0.5);
+        assert_eq!(radical_inverse(2, 6), 0.375);
+        assert_abs_diff_eq!(radical_inverse(3, 5), 7. / 9.);
+    }
+
+    #[test]
+    fn halton_samples_are_spread_over_a_pixel() {
+        let mut sampler = HaltonSampler::new(0);
+        let mut halves = [0; 2];
+        let mut thirds = [0; 3];
+
+        for index in 0..6 {
+            sampler.start_pixel_sample((2, 3), index);
+            let (u, v) = sampler.next_2d();
+            halves[(u * 2.) as usize] += 1;
+            thirds[(v * 3.) as usize] += 1;
+        }
+
+        assert_eq!(halves, [3; 2]);
+        assert_eq!(thirds, [2; 3]);
+    }
+
+    #[test]
+    fn halton_samples_are_repeatable() {
+        let mut a = HaltonSampler::new(9);
+        let mut b = HaltonSampler::new(9);
+        b.start_pixel_sample((1, 1), 5);
+        b.next_1d();
+
+        a.start_pixel_sample((1, 2), 3);
+        b.start_pixel_sample((1, 2), 3);
+
+        let xs: Vec<Scalar> = (0..20).map(|_| a.next_1d()).collect();
+        let ys: Vec<Scalar> = (0..20).map(|_| b.next_1d()).collect();
+        assert_eq!(xs, ys);
+        assert!(xs.iter().all(|x| (0. ..1.).contains(x)));
+    }
+}
@@ -0,0 +1,147 @@
+use super::{hash, Sampler};
+use crate::tuples::Scalar;
+
+/// The first two dimensions of the Sobol sequence, which together form a (0, 2)
+/// sequence in base 2, used for every pair of dimensions. Each pixel and dimension
+/// gets its own shuffle of the sample indices and its own random digital shift, which
+/// keeps the points of a pixel well distributed when the number of samples per pixel
+/// is the power of two given on construction.
+#[derive(Debug, Clone)]
+pub struct SobolSampler {
+    seed: u64,
+    samples_per_pixel: usize,
+    pixel: (usize, usize),
+    index: usize,
+    dimension: usize,
+}
+
+impl SobolSampler {
+    /// `samples_per_pixel` is rounded up to a power of two.
+    pub fn new(samples_per_pixel: usize, seed: u64) -> SobolSampler {
+        SobolSampler {
+            seed,
+            samples_per_pixel: samples_per_pixel.max(1).next_power_of_two(),
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// The shuffled index and the digital shift of the current dimension.
+    fn scramble(&mut self) -> (u32, u32) {
+        let key = hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+        ]);
+        let mask = (self.samples_per_pixel - 1) as u64;
+        let index = (self.index as u64 ^ (key & mask)) as u32;
+        (index, (key >> 32) as u32)
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn next_1d(&mut self) -> Scalar {
+        let (index, shift) = self.scramble();
+        self.dimension += 1;
+        to_unit(first_dimension(index) ^ shift)
+    }
+
+    fn next_2d(&mut self) -> (Scalar, Scalar) {
+        let (index, shift) = self.scramble();
+        let second_shift = hash(&[shift as u64]) as u32;
+        self.dimension += 2;
+        (
+            to_unit(first_dimension(index) ^ shift),
+            to_unit(second_dimension(index) ^ second_shift),
+        )
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), index: usize) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
+
+/// The van der Corput sequence in base 2.
+fn first_dimension(index: u32) -> u32 {
+    index.reverse_bits()
+}
+
+/// Sobol's second dimension, whose direction numbers come from the polynomial x + 1.
+fn second_dimension(mut index: u32) -> u32 {
+    let mut result = 0;
+    let mut direction = 1 << 31;
+    while index > 0 {
+        if index & 1 == 1 {
+            result ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    result
+}
+
+fn to_unit(value: u32) -> Scalar {
+    value as Scalar / (1u64 << 32) as Scalar
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn the_first_points_of_the_sobol_sequence() {
+        let points: Vec<(Scalar, Scalar)> = (0..4)
+            .map(|i| (to_unit(first_dimension(i)), to_unit(second_dimension(i))))
+            .collect();
+
+        assert_eq!(
+            points,
+            vec![(0., 0.), (0.5, 0.5), (0.25, 0.75), (0.75, 0.25)]
+        );
+    }
+
+    #[test]
+    fn sobol_samples_fill_every_elementary_interval() {
+        for (columns, rows) in [(16, 1), (4, 4), (2, 8), (1, 16)] {
+            let mut sampler = SobolSampler::new(16, 3);
+            let mut cells = vec![0; 16];
+
+            for index in 0..16 {
+                sampler.start_pixel_sample((5, 7), index);
+                let (u, v) = sampler.next_2d();
+                let (x, y) = (
+                    (u * columns as Scalar) as usize,
+                    (v * rows as Scalar) as usize,
+                );
+                cells[x + columns * y] += 1;
+            }
+
+            assert_eq!(cells, vec![1; 16]);
+        }
+    }
+
+    #[test]
+    fn dimensions_are_shuffled_independently() {
+        let mut sampler = SobolSampler::new(16, 3);
+
+        let pairs: Vec<(Scalar, Scalar)> = (0..16)
+            .map(|index| {
+                sampler.start_pixel_sample((0, 0), index);
+                let (u, _) = sampler.next_2d();
+                let (w, _) = sampler.next_2d();
+                (u, w)
+            })
+            .collect();
+
+        assert!(pairs.iter().any(|(u, w)| (u - w).abs() > 1e-3));
+    }
+}
@@ -0,0 +1,183 @@
+use super::{hash, RandomSampler, Sampler};
+use crate::tuples::Scalar;
+
+/// Divides every dimension of a pixel into strata, one per sample, and visits them in
+/// a random order per pixel and dimension so that the strata of different dimensions
+/// are not paired up the same way. Pairs of dimensions are split into a grid of
+/// `x_strata` by `y_strata` cells. Without jitter every sample lies at the centre of
+/// its stratum.
+#[derive(Debug, Clone)]
+pub struct StratifiedSampler {
+    x_strata: usize,
+    y_strata: usize,
+    jitter: bool,
+    seed: u64,
+    pixel: (usize, usize),
+    index: usize,
+    dimension: usize,
+    random: RandomSampler,
+}
+
+impl StratifiedSampler {
+    pub fn new(x_strata: usize, y_strata: usize, jitter: bool, seed: u64) -> StratifiedSampler {
+        StratifiedSampler {
+            x_strata: x_strata.max(1),
+            y_strata: y_strata.max(1),
+            jitter,
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+            random: RandomSampler::new(seed),
+        }
+    }
+
+    pub fn samples_per_pixel(&self) -> usize {
+        self.x_strata * self.y_strata
+    }
+
+    /// The stratum of the current sample in the current dimension.
+    fn stratum(&self) -> usize {
+        let count = self.samples_per_pixel();
+        let key = hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+        ]);
+        permute((self.index % count) as u32, count as u32, key as u32) as usize
+    }
+
+    fn offset(&mut self) -> Scalar {
+        if self.jitter {
+            self.random.next_1d()
+        } else {
+            0.5
+        }
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn next_1d(&mut self) -> Scalar {
+        let stratum = self.stratum();
+        self.dimension += 1;
+        (stratum as Scalar + self.offset()) / self.samples_per_pixel() as Scalar
+    }
+
+    fn next_2d(&mut self) -> (Scalar, Scalar) {
+        let stratum = self.stratum();
+        self.dimension += 2;
+        let (x, y) = (stratum % self.x_strata, stratum / self.x_strata);
+        (
+            (x as Scalar + self.offset()) / self.x_strata as Scalar,
+            (y as Scalar + self.offset()) / self.y_strata as Scalar,
+        )
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (usize, usize), index: usize) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+        self.random.start_pixel_sample(pixel, index);
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Kensler's hashed permutation: the element at `index` of a permutation of
+/// `0..length` chosen by `key`, found without building the permutation.
+fn permute(mut index: u32, length: u32, key: u32) -> u32 {
+    let mut mask = length - 1;
+    mask |= mask >> 1;
+    mask |= mask >> 2;
+    mask |= mask >> 4;
+    mask |= mask >> 8;
+    mask |= mask >> 16;
+    loop {
+        index ^= key;
+        index = index.wrapping_mul(0xe170893d);
+        index ^= key >> 16;
+        index ^= (index & mask) >> 4;
+        index ^= key >> 8;
+        index = index.wrapping_mul(0x0929eb3f);
+        index ^= key >> 23;
+        index ^= (index & mask) >> 1;
+        index = index.wrapping_mul(1 | key >> 27);
+        index = index.wrapping_mul(0x6935fa69);
+        index ^= (index & mask) >> 11;
+        index = index.wrapping_mul(0x74dcb303);
+        index ^= (index & mask) >> 2;
+        index = index.wrapping_mul(0x9e501cc3);
+        index ^= (index & mask) >> 2;
+        index = index.wrapping_mul(0xc860a3df);
+        index &= mask;
+        index ^= index >> 5;
+        if index < length {
+            return index.wrapping_add(key) % length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn permuting_indices_gives_every_one_once() {
+        for (length, key) in [(1, 7), (6, 1), (16, 0xdead_beef), (100, 42)] {
+            let mut values: Vec<u32> = (0..length).map(|i| permute(i, length, key)).collect();
+            values.sort();
+
+            assert_eq!(values, (0..length).collect::<Vec<_>>());
+        }
+        assert_ne!(
+            (0..16).map(|i| permute(i, 16, 1)).collect::<Vec<_>>(),
+            (0..16).map(|i| permute(i, 16, 2)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn every_cell_of_a_pixel_gets_one_sample() {
+        let mut sampler = StratifiedSampler::new(3, 2, true, 1);
+        let mut cells = [0; 6];
+
+        for index in 0..sampler.samples_per_pixel() {
+            sampler.start_pixel_sample((4, 4), index);
+            let (u, v) = sampler.next_2d();
+            cells[(u * 3.) as usize + 3 * (v * 2.) as usize] += 1;
+        }
+
+        assert_eq!(cells, [1; 6]);
+    }
+
+    #[test]
+    fn without_jitter_samples_lie_at_the_centres_of_strata() {
+        let mut sampler = StratifiedSampler::new(2, 2, false, 1);
+        let mut values: Vec<Scalar> = (0..4)
+            .map(|index| {
+                sampler.start_pixel_sample((0, 0), index);
+                sampler.next_1d()
+            })
+            .collect();
+        values.sort_by(Scalar::total_cmp);
+
+        assert_eq!(values, vec![0.125, 0.375, 0.625, 0.875]);
+    }
+
+    #[test]
+    fn stratified_samples_are_repeatable() {
+        let mut a = StratifiedSampler::new(4, 4, true, 2);
+        let mut b = StratifiedSampler::new(4, 4, true, 2);
+        b.start_pixel_sample((9, 9), 1);
+        b.next_2d();
+
+        a.start_pixel_sample((1, 2), 3);
+        b.start_pixel_sample((1, 2), 3);
+
+        assert_eq!(a.next_2d(), b.next_2d());
+        assert_eq!(a.next_1d(), b.next_1d());
+    }
+}
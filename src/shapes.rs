@@ -2,7 +2,7 @@ use crate::{
     intersections::Intersection,
     materials::Material,
     rays::Ray,
-    transformations::Transformation,
+    transformations::{Decomposition, Transformation},
     tuples::{Point, Scalar, Vector},
 };
 use std::fmt::Debug;
@@ -21,7 +21,11 @@ pub trait ShapeType: Debug {
 pub struct Shape {
     transform: Transformation,
     inversed_transform: Transformation,
+    motion: Option<(Decomposition, Decomposition)>,
     material: Material,
+    casts_shadow: bool,
+    receives_shadow: bool,
+    visible: bool,
     shape_type: Box<dyn ShapeType>,
 }
 
@@ -38,7 +42,11 @@ impl Shape {
         Shape {
             transform: Transformation::IDENTITY,
             inversed_transform: Transformation::IDENTITY,
+            motion: None,
             material: Material::default(),
+            casts_shadow: true,
+            receives_shadow: true,
+            visible: true,
             shape_type,
         }
     }
@@ -54,6 +62,32 @@ impl Shape {
     pub fn set_transform(&mut self, transform: Transformation) {
         self.transform = transform;
         self.inversed_transform = transform.inverse();
+        self.motion = None;
+    }
+
+    /// Moves the shape from `start` at time 0 to `end` at time 1, interpolating the
+    /// translation, rotation and stretch of the two transforms in between.
+    pub fn set_motion(&mut self, start: Transformation, end: Transformation) {
+        self.set_transform(start);
+        self.motion = Some((Decomposition::new(&start), Decomposition::new(&end)));
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.motion.is_some()
+    }
+
+    pub fn transform_at(&self, time: Scalar) -> Transformation {
+        match &self.motion {
+            Some((start, end)) if time != 0. => start.interpolate(end, time).transformation(),
+            _ => self.transform,
+        }
+    }
+
+    pub fn inversed_transform_at(&self, time: Scalar) -> Transformation {
+        match &self.motion {
+            Some(_) if time != 0. => self.transform_at(time).inverse(),
+            _ => self.inversed_transform,
+        }
     }
 
     pub fn material(&self) -> &Material {
@@ -64,8 +98,32 @@ impl Shape {
         &mut self.material
     }
 
-    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
-        let local_ray = ray.transform(&self.inversed_transform);
+    pub fn casts_shadow(&self) -> bool {
+        self.casts_shadow
+    }
+
+    pub fn set_casts_shadow(&mut self, casts_shadow: bool) {
+        self.casts_shadow = casts_shadow;
+    }
+
+    pub fn receives_shadow(&self) -> bool {
+        self.receives_shadow
+    }
+
+    pub fn set_receives_shadow(&mut self, receives_shadow: bool) {
+        self.receives_shadow = receives_shadow;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let local_ray = ray.transform(&self.inversed_transform_at(ray.time));
         self.shape_type
             .local_intersect(&local_ray)
             .iter()
@@ -74,9 +132,14 @@ impl Shape {
     }
 
     pub fn normal_at(&self, world_point: &Point) -> Vector {
-        let object_point = self.inversed_transform * *world_point;
+        self.normal_at_time(world_point, 0.)
+    }
+
+    pub fn normal_at_time(&self, world_point: &Point, time: Scalar) -> Vector {
+        let inversed_transform = self.inversed_transform_at(time);
+        let object_point = inversed_transform * *world_point;
         let object_normal = self.shape_type.local_normal_at(&object_point);
-        let mut world_normal = self.inversed_transform.transpose() * object_normal;
+        let mut world_normal = inversed_transform.transpose() * object_normal;
         world_normal.w = 0.;
         world_normal.normalize()
     }
@@ -119,6 +182,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn a_sphere_may_be_assigned_a_material() {
         let mut s = Shape::sphere();
         let mut m = Material::default();
@@ -129,6 +193,28 @@ mod tests {
         assert_eq!(s.material.ambient, 1.);
     }
 
+    #[test]
+    fn a_shape_casts_and_receives_shadows_and_is_visible_by_default() {
+        let s = Shape::sphere();
+
+        assert!(s.casts_shadow());
+        assert!(s.receives_shadow());
+        assert!(s.is_visible());
+    }
+
+    #[test]
+    fn changing_shadow_and_visibility_flags() {
+        let mut s = Shape::sphere();
+
+        s.set_casts_shadow(false);
+        s.set_receives_shadow(false);
+        s.set_visible(false);
+
+        assert!(!s.casts_shadow());
+        assert!(!s.receives_shadow());
+        assert!(!s.is_visible());
+    }
+
     #[test]
     fn intersect_sets_the_object_on_the_intersection() {
         let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
@@ -167,6 +253,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn computing_normal_on_a_translated_shape() {
         let mut s = Shape::sphere();
         s.set_transform(Transformation::translation(0., 1., 0.));
@@ -186,4 +273,83 @@ mod tests {
 
         assert_abs_diff_eq!(n, Tuple::vector(0., 0.97014, -0.24254), epsilon = 0.00001);
     }
+
+    #[test]
+    fn a_shape_is_static_by_default() {
+        let mut s = Shape::sphere();
+        s.set_transform(Transformation::translation(1., 0., 0.));
+
+        assert!(!s.is_moving());
+        assert_eq!(s.inversed_transform_at(0.7), *s.inversed_transform());
+    }
+
+    #[test]
+    fn intersecting_a_moving_shape_at_different_times() {
+        let mut s = Shape::sphere();
+        s.set_motion(
+            Transformation::translation(-4., 0., 0.),
+            Transformation::translation(4., 0., 0.),
+        );
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
+
+        assert!(s.is_moving());
+        assert_eq!(s.intersect(&r).len(), 0);
+        let xs = s.intersect(&Ray::new(r.origin, r.direction).with_time(0.5));
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.);
+        assert_eq!(
+            s.intersect(&Ray::new(r.origin, r.direction).with_time(1.))
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn the_normal_of_a_moving_shape_follows_it() {
+        let mut s = Shape::sphere();
+        s.set_motion(
+            Transformation::translation(0., 0., 0.),
+            Transformation::translation(2., 0., 0.),
+        );
+
+        let n = s.normal_at_time(&Tuple::point(2., 1., 0.), 1.);
+
+        assert_abs_diff_eq!(n, Tuple::vector(0., 1., 0.));
+    }
+
+    #[test]
+    fn a_rotating_shape_keeps_its_size() {
+        let mut s = Shape::sphere();
+        s.set_motion(
+            Transformation::scaling(2., 1., 1.),
+            Transformation::rotation_y(PI) * Transformation::scaling(2., 1., 1.),
+        );
+
+        let along_x = s.intersect(
+            &Ray::new(Tuple::point(-5., 0., 0.), Tuple::vector(1., 0., 0.)).with_time(0.5),
+        );
+        let along_z = s.intersect(
+            &Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.)).with_time(0.5),
+        );
+
+        assert_eq!(along_x.len(), 2);
+        assert_abs_diff_eq!(along_x[0].t, 4., epsilon = 0.00001);
+        assert_abs_diff_eq!(along_x[1].t, 6., epsilon = 0.00001);
+        assert_eq!(along_z.len(), 2);
+        assert_abs_diff_eq!(along_z[0].t, 3., epsilon = 0.00001);
+        assert_abs_diff_eq!(along_z[1].t, 7., epsilon = 0.00001);
+    }
+
+    #[test]
+    fn setting_a_transform_stops_a_moving_shape() {
+        let mut s = Shape::sphere();
+        s.set_motion(
+            Transformation::IDENTITY,
+            Transformation::scaling(2., 2., 2.),
+        );
+
+        s.set_transform(Transformation::IDENTITY);
+
+        assert!(!s.is_moving());
+    }
 }
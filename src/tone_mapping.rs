@@ -0,0 +1,201 @@
+use crate::{
+    sampling::{RandomSampler, Sampler},
+    tuples::{Color, Scalar, Tuple},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMapping {
+    Clamp,
+    Reinhard,
+    Aces,
+    Filmic,
+}
+
+impl ToneMapping {
+    pub fn map(&self, value: Scalar) -> Scalar {
+        let value = value.max(0.);
+        let mapped = match self {
+            ToneMapping::Clamp => value,
+            ToneMapping::Reinhard => value / (1. + value),
+            ToneMapping::Aces => {
+                (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14)
+            }
+            ToneMapping::Filmic => hable(value * FILMIC_EXPOSURE_BIAS) / hable(FILMIC_WHITE),
+        };
+        mapped.clamp(0., 1.)
+    }
+}
+
+const FILMIC_EXPOSURE_BIAS: Scalar = 2.;
+const FILMIC_WHITE: Scalar = 11.2;
+
+fn hable(x: Scalar) -> Scalar {
+    let (a, b, c, d, e, f) = (0.15, 0.5, 0.1, 0.2, 0.02, 0.3);
+    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
+
+pub fn srgb_encode(value: Scalar) -> Scalar {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Conversion of linear scene values into display values. `exposure` is in stops.
+/// The default keeps values as they are apart from clamping them to the displayable
+/// range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputTransform {
+    pub exposure: Scalar,
+    pub tone_mapping: ToneMapping,
+    pub srgb: bool,
+    pub dither: bool,
+}
+
+impl OutputTransform {
+    pub fn display() -> OutputTransform {
+        OutputTransform {
+            exposure: 0.,
+            tone_mapping: ToneMapping::Aces,
+            srgb: true,
+            dither: true,
+        }
+    }
+
+    pub fn apply(&self, color: &Color) -> Color {
+        let scale = (2 as Scalar).powf(self.exposure);
+        let encode = |value: Scalar| {
+            let mapped = self.tone_mapping.map(value * scale);
+            if self.srgb {
+                srgb_encode(mapped)
+            } else {
+                mapped
+            }
+        };
+        Tuple::color(
+            encode(color.red()),
+            encode(color.green()),
+            encode(color.blue()),
+        )
+    }
+
+    /// Rounds a display value to an integer in `0..=max`. With dithering a small
+    /// amount of noise, fixed for every `seed`, hides banding in smooth gradients.
+    pub fn quantize(&self, value: Scalar, max: u16, seed: u64) -> u16 {
+        let max = max as Scalar;
+        let noise = if self.dither {
+            RandomSampler::new(seed).next_1d() - 0.5
+        } else {
+            0.
+        };
+        (value * max + noise).round().clamp(0., max) as u16
+    }
+}
+
+impl Default for OutputTransform {
+    fn default() -> Self {
+        OutputTransform {
+            exposure: 0.,
+            tone_mapping: ToneMapping::Clamp,
+            srgb: false,
+            dither: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn the_default_transform_only_clamps() {
+        let t = OutputTransform::default();
+
+        assert_eq!(
+            t.apply(&Tuple::color(-0.5, 0.5, 1.5)),
+            Tuple::color(0., 0.5, 1.)
+        );
+    }
+
+    #[test]
+    fn exposure_is_measured_in_stops() {
+        let t = OutputTransform {
+            exposure: 1.,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            t.apply(&Tuple::color(0.25, 0.125, 0.)),
+            Tuple::color(0.5, 0.25, 0.)
+        );
+    }
+
+    #[test]
+    fn tone_mapping_compresses_highlights_into_the_displayable_range() {
+        for tone_mapping in [
+            ToneMapping::Reinhard,
+            ToneMapping::Aces,
+            ToneMapping::Filmic,
+        ] {
+            let mut previous = tone_mapping.map(0.);
+            assert_abs_diff_eq!(previous, 0., epsilon = 0.001);
+            for value in [0.1, 0.5, 1., 2., 8., 100.] {
+                let mapped = tone_mapping.map(value);
+                assert!(mapped >= previous, "{tone_mapping:?} at {value}");
+                assert!(mapped <= 1.);
+                previous = mapped;
+            }
+            assert!(tone_mapping.map(1.) < 1.);
+        }
+    }
+
+    #[test]
+    fn reinhard_tone_mapping() {
+        assert_eq!(ToneMapping::Reinhard.map(1.), 0.5);
+        assert_eq!(ToneMapping::Reinhard.map(3.), 0.75);
+    }
+
+    #[test]
+    fn filmic_tone_mapping_reaches_white_at_the_white_point() {
+        assert_abs_diff_eq!(
+            ToneMapping::Filmic.map(FILMIC_WHITE / FILMIC_EXPOSURE_BIAS),
+            1.
+        );
+    }
+
+    #[test]
+    fn srgb_encoding() {
+        assert_eq!(srgb_encode(0.), 0.);
+        assert_abs_diff_eq!(srgb_encode(0.002), 0.02584);
+        assert_abs_diff_eq!(srgb_encode(0.5), 0.735357, epsilon = 0.000001);
+        assert_abs_diff_eq!(srgb_encode(1.), 1.);
+    }
+
+    #[test]
+    fn quantizing_without_dithering_rounds() {
+        let t = OutputTransform::default();
+
+        assert_eq!(t.quantize(0.5, 255, 0), 128);
+        assert_eq!(t.quantize(1., 255, 0), 255);
+        assert_eq!(t.quantize(1., 65535, 0), 65535);
+    }
+
+    #[test]
+    fn dithering_preserves_the_average_level() {
+        let t = OutputTransform {
+            dither: true,
+            ..Default::default()
+        };
+        let value = 100.25 / 255.;
+
+        let levels: Vec<u16> = (0..1000).map(|seed| t.quantize(value, 255, seed)).collect();
+        let mean = levels.iter().map(|l| *l as Scalar).sum::<Scalar>() / 1000.;
+
+        assert!(levels.iter().all(|l| *l == 100 || *l == 101));
+        assert_abs_diff_eq!(mean, 100.25, epsilon = 0.05);
+        assert_eq!(t.quantize(value, 255, 7), t.quantize(value, 255, 7));
+    }
+}
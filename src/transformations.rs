@@ -3,6 +3,10 @@ use crate::{
     tuples::{Point, Scalar, Vector},
 };
 
+mod interpolation;
+
+pub use interpolation::Decomposition;
+
 pub type Transformation = Matrix4;
 
 impl Transformation {
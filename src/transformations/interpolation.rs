@@ -0,0 +1,280 @@
+use super::Transformation;
+use crate::{
+    matrices::{Matrix3, Matrix4},
+    tuples::{Scalar, Tuple, Vector},
+};
+
+/// A transformation split into a translation, a rotation and a stretch, applied in
+/// reverse order. Interpolating the parts separately keeps rotations rigid, where
+/// interpolating the matrices element by element would shrink them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decomposition {
+    translation: Vector,
+    rotation: Quaternion,
+    stretch: Transformation,
+}
+
+impl Decomposition {
+    /// Expects an affine transformation whose linear part is invertible.
+    pub fn new(transform: &Transformation) -> Decomposition {
+        let translation = Tuple::vector(transform[(0, 3)], transform[(1, 3)], transform[(2, 3)]);
+        let linear = Transformation::translation(-translation.x, -translation.y, -translation.z)
+            * *transform;
+
+        // Polar decomposition: averaging a matrix with its inverse transpose converges
+        // to the closest rotation.
+        let mut rotation = linear;
+        for _ in 0..100 {
+            let next = rotation.lerp(&rotation.transpose().inverse(), 0.5);
+            let change = (0..3)
+                .flat_map(|i| (0..3).map(move |j| (i, j)))
+                .map(|index| (next[index] - rotation[index]).abs())
+                .fold(0., Scalar::max);
+            rotation = next;
+            if change < 1e-12 {
+                break;
+            }
+        }
+        let mut stretch = rotation.inverse() * linear;
+        if determinant(&rotation) < 0. {
+            // A reflection is not a rotation; move it into the stretch.
+            let flip = Transformation::scaling(-1., -1., -1.);
+            rotation = rotation * flip;
+            stretch = flip * stretch;
+        }
+
+        Decomposition {
+            translation,
+            rotation: Quaternion::from_rotation(&rotation),
+            stretch,
+        }
+    }
+
+    /// Moves the translation and the stretch linearly and the rotation along the
+    /// shortest arc.
+    pub fn interpolate(&self, other: &Decomposition, t: Scalar) -> Decomposition {
+        Decomposition {
+            translation: self.translation + (other.translation - self.translation) * t,
+            rotation: self.rotation.slerp(&other.rotation, t),
+            stretch: self.stretch.lerp(&other.stretch, t),
+        }
+    }
+
+    pub fn transformation(&self) -> Transformation {
+        Transformation::translation(self.translation.x, self.translation.y, self.translation.z)
+            * self.rotation.rotation()
+            * self.stretch
+    }
+}
+
+impl Transformation {
+    /// Interpolates between two transformations through their decompositions.
+    pub fn interpolate(&self, other: &Transformation, t: Scalar) -> Transformation {
+        Decomposition::new(self)
+            .interpolate(&Decomposition::new(other), t)
+            .transformation()
+    }
+}
+
+fn determinant(m: &Matrix4) -> Scalar {
+    Matrix3::new(
+        m[(0, 0)],
+        m[(0, 1)],
+        m[(0, 2)],
+        m[(1, 0)],
+        m[(1, 1)],
+        m[(1, 2)],
+        m[(2, 0)],
+        m[(2, 1)],
+        m[(2, 2)],
+    )
+    .determinant()
+}
+
+/// A unit quaternion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Quaternion {
+    w: Scalar,
+    x: Scalar,
+    y: Scalar,
+    z: Scalar,
+}
+
+impl Quaternion {
+    fn from_rotation(m: &Matrix4) -> Quaternion {
+        let trace = m[(0, 0)] + m[(1, 1)] + m[(2, 2)];
+        let q = if trace > 0. {
+            let s = 2. * (trace + 1.).sqrt();
+            Quaternion {
+                w: s / 4.,
+                x: (m[(2, 1)] - m[(1, 2)]) / s,
+                y: (m[(0, 2)] - m[(2, 0)]) / s,
+                z: (m[(1, 0)] - m[(0, 1)]) / s,
+            }
+        } else if m[(0, 0)] > m[(1, 1)] && m[(0, 0)] > m[(2, 2)] {
+            let s = 2. * (1. + m[(0, 0)] - m[(1, 1)] - m[(2, 2)]).sqrt();
+            Quaternion {
+                w: (m[(2, 1)] - m[(1, 2)]) / s,
+                x: s / 4.,
+                y: (m[(0, 1)] + m[(1, 0)]) / s,
+                z: (m[(0, 2)] + m[(2, 0)]) / s,
+            }
+        } else if m[(1, 1)] > m[(2, 2)] {
+            let s = 2. * (1. + m[(1, 1)] - m[(0, 0)] - m[(2, 2)]).sqrt();
+            Quaternion {
+                w: (m[(0, 2)] - m[(2, 0)]) / s,
+                x: (m[(0, 1)] + m[(1, 0)]) / s,
+                y: s / 4.,
+                z: (m[(1, 2)] + m[(2, 1)]) / s,
+            }
+        } else {
+            let s = 2. * (1. + m[(2, 2)] - m[(0, 0)] - m[(1, 1)]).sqrt();
+            Quaternion {
+                w: (m[(1, 0)] - m[(0, 1)]) / s,
+                x: (m[(0, 2)] + m[(2, 0)]) / s,
+                y: (m[(1, 2)] + m[(2, 1)]) / s,
+                z: s / 4.,
+            }
+        };
+        q.scale(1. / q.dot(&q).sqrt())
+    }
+
+    fn dot(&self, other: &Quaternion) -> Scalar {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn scale(&self, factor: Scalar) -> Quaternion {
+        Quaternion {
+            w: self.w * factor,
+            x: self.x * factor,
+            y: self.y * factor,
+            z: self.z * factor,
+        }
+    }
+
+    fn add(&self, other: &Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w + other.w,
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+
+    fn slerp(&self, other: &Quaternion, t: Scalar) -> Quaternion {
+        let mut cos = self.dot(other);
+        let mut other = *other;
+        if cos < 0. {
+            // q and -q are the same rotation; take the shorter way round.
+            other = other.scale(-1.);
+            cos = -cos;
+        }
+        let result = if cos > 0.9995 {
+            self.scale(1. - t).add(&other.scale(t))
+        } else {
+            let angle = cos.acos();
+            self.scale(((1. - t) * angle).sin())
+                .add(&other.scale((t * angle).sin()))
+        };
+        result.scale(1. / result.dot(&result).sqrt())
+    }
+
+    fn rotation(&self) -> Transformation {
+        let Quaternion { w, x, y, z } = *self;
+        Matrix4::new(
+            1. - 2. * (y * y + z * z),
+            2. * (x * y - w * z),
+            2. * (x * z + w * y),
+            0.,
+            2. * (x * y + w * z),
+            1. - 2. * (x * x + z * z),
+            2. * (y * z - w * x),
+            0.,
+            2. * (x * z - w * y),
+            2. * (y * z + w * x),
+            1. - 2. * (x * x + y * y),
+            0.,
+            0.,
+            0.,
+            0.,
+            1.,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn decomposing_and_recomposing_a_transformation() {
+        let m = Transformation::translation(1., -2., 3.)
+            * Transformation::rotation_x(0.4)
+            * Transformation::rotation_z(-1.1)
+            * Transformation::scaling(2., 0.5, 3.);
+
+        assert_abs_diff_eq!(
+            Decomposition::new(&m).transformation(),
+            m,
+            epsilon = 0.00001
+        );
+    }
+
+    #[test]
+    fn decomposing_a_reflection() {
+        let m = Transformation::rotation_y(0.3) * Transformation::scaling(-1., 2., 1.);
+
+        assert_abs_diff_eq!(
+            Decomposition::new(&m).transformation(),
+            m,
+            epsilon = 0.00001
+        );
+    }
+
+    #[test]
+    fn interpolating_a_rotation_keeps_it_rigid() {
+        let a = Transformation::IDENTITY;
+        let b = Transformation::rotation_y(PI / 2.);
+
+        assert_abs_diff_eq!(
+            a.interpolate(&b, 0.5),
+            Transformation::rotation_y(PI / 4.),
+            epsilon = 0.00001
+        );
+    }
+
+    #[test]
+    fn interpolating_half_a_turn() {
+        let a = Transformation::rotation_z(0.);
+        let b = Transformation::rotation_z(PI);
+
+        let m = a.interpolate(&b, 0.5);
+
+        assert_abs_diff_eq!(
+            m * Tuple::vector(1., 0., 0.),
+            Tuple::vector(0., 1., 0.),
+            epsilon = 0.00001
+        );
+        assert_abs_diff_eq!(m.inverse() * m, Transformation::IDENTITY, epsilon = 0.00001);
+    }
+
+    #[test]
+    fn interpolating_translation_rotation_and_scaling_together() {
+        let a = Transformation::translation(0., 0., 0.) * Transformation::scaling(1., 1., 1.);
+        let b = Transformation::translation(4., 2., 0.)
+            * Transformation::rotation_x(PI / 2.)
+            * Transformation::scaling(3., 3., 3.);
+
+        assert_abs_diff_eq!(
+            a.interpolate(&b, 0.5),
+            Transformation::translation(2., 1., 0.)
+                * Transformation::rotation_x(PI / 4.)
+                * Transformation::scaling(2., 2., 2.),
+            epsilon = 0.00001
+        );
+        assert_abs_diff_eq!(a.interpolate(&b, 1.), b, epsilon = 0.00001);
+    }
+}
@@ -77,7 +77,13 @@ impl Tuple {
         self.z
     }
 
+    /// Relative luminance with the Rec. 709 weights.
+    pub fn luminance(&self) -> Scalar {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
+
     pub const BLACK: Color = Self::color(0., 0., 0.);
+    pub const WHITE: Color = Self::color(1., 1., 1.);
 }
 
 impl ops::Add<Tuple> for Tuple {
@@ -400,6 +406,13 @@ mod tests {
         assert_abs_diff_eq!(c1 * c2, Tuple::color(0.9, 0.2, 0.04));
     }
 
+    #[test]
+    fn luminance_of_colors() {
+        assert_abs_diff_eq!(Tuple::color(1., 1., 1.).luminance(), 1.);
+        assert_abs_diff_eq!(Tuple::color(0., 1., 0.).luminance(), 0.7152);
+        assert_eq!(Tuple::color(0., 0., 0.).luminance(), 0.);
+    }
+
     #[test]
     fn reflecting_a_vector_approaching_at_45_deg() {
         let v = Tuple::vector(1., -1., 0.);
@@ -1,16 +1,60 @@
 use crate::{
+    backgrounds::{Background, Constant},
     intersections::{hit, intersections, Computations, Intersection},
     lights::PointLight,
     materials::Material,
     rays::Ray,
+    sampling::{cosine_hemisphere, RandomSampler, Sampler},
     shapes::Shape,
     transformations::Transformation,
-    tuples::{Color, Point, Tuple},
+    tuples::{Color, Point, Scalar, Tuple, Vector},
 };
+use std::{f64::consts::PI, ptr};
+
+/// Fraction of the hemisphere above a surface that is free of shadow casting objects
+/// up to `max_distance`. As an integrator it renders that fraction in greyscale, with
+/// rays that miss everything counting as fully unoccluded.
+#[derive(Debug, Clone, Copy)]
+pub struct AmbientOcclusion {
+    pub samples: usize,
+    pub max_distance: Scalar,
+}
+
+impl AmbientOcclusion {
+    pub fn new(samples: usize, max_distance: Scalar) -> AmbientOcclusion {
+        AmbientOcclusion {
+            samples,
+            max_distance,
+        }
+    }
+
+    pub fn accessibility(
+        &self,
+        world: &World,
+        comps: &Computations,
+        sampler: &mut dyn Sampler,
+    ) -> Scalar {
+        if self.samples == 0 {
+            return 1.;
+        }
+        let unoccluded = (0..self.samples)
+            .filter(|_| {
+                let direction = cosine_hemisphere(sampler.next_2d(), &comps.normalv);
+                let ray = Ray::new(comps.over_point, direction).with_time(comps.time);
+                !world.is_occluded(&ray, self.max_distance)
+            })
+            .count();
+        unoccluded as Scalar / self.samples as Scalar
+    }
+}
 
 pub struct World {
     objects: Vec<Shape>,
     lights: Vec<PointLight>,
+    ambient_occlusion: Option<AmbientOcclusion>,
+    background: Box<dyn Background>,
+    background_lighting: bool,
+    environment_samples: usize,
 }
 
 impl World {
@@ -18,6 +62,10 @@ impl World {
         World {
             objects: vec![],
             lights: vec![],
+            ambient_occlusion: None,
+            background: Box::new(Constant::new(Color::BLACK)),
+            background_lighting: false,
+            environment_samples: 0,
         }
     }
 
@@ -25,57 +73,207 @@ impl World {
         World {
             objects,
             lights: vec![light],
+            ambient_occlusion: None,
+            background: Box::new(Constant::new(Color::BLACK)),
+            background_lighting: false,
+            environment_samples: 0,
         }
     }
 
-    fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+    pub fn set_ambient_occlusion(&mut self, ambient_occlusion: Option<AmbientOcclusion>) {
+        self.ambient_occlusion = ambient_occlusion;
+    }
+
+    pub fn background(&self) -> &dyn Background {
+        self.background.as_ref()
+    }
+
+    pub fn set_background(&mut self, background: Box<dyn Background>) {
+        self.background = background;
+    }
+
+    pub fn background_lighting(&self) -> bool {
+        self.background_lighting
+    }
+
+    /// Lets the background light the scene in addition to being seen by rays that miss.
+    pub fn set_background_lighting(&mut self, background_lighting: bool) {
+        self.background_lighting = background_lighting;
+    }
+
+    /// Number of shadow rays towards the background per shading point when it lights
+    /// the scene. With none, diffuse surfaces take the background in the direction
+    /// of their normal, without shadows.
+    pub fn set_environment_samples(&mut self, environment_samples: usize) {
+        self.environment_samples = environment_samples;
+    }
+
+    pub fn objects(&self) -> &[Shape] {
+        &self.objects
+    }
+
+    pub fn objects_mut(&mut self) -> &mut [Shape] {
+        &mut self.objects
+    }
+
+    pub fn object_index(&self, object: &Shape) -> Option<usize> {
+        self.objects.iter().position(|o| ptr::eq(o, object))
+    }
+
+    /// For every object, the index of the first object sharing its material.
+    pub fn material_ids(&self) -> Vec<usize> {
+        self.objects
+            .iter()
+            .map(|object| {
+                self.objects
+                    .iter()
+                    .position(|o| o.material() == object.material())
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    pub fn lights(&self) -> &[PointLight] {
+        &self.lights
+    }
+
+    pub fn lights_mut(&mut self) -> &mut [PointLight] {
+        &mut self.lights
+    }
+
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        self.intersect_matching(ray, |_| true)
+    }
+
+    pub fn intersect_visible(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        self.intersect_matching(ray, Shape::is_visible)
+    }
+
+    fn intersect_matching<F>(&self, ray: &Ray, predicate: F) -> Vec<Intersection<'_>>
+    where
+        F: Fn(&Shape) -> bool,
+    {
         let mut result = vec![];
-        for object in &self.objects {
+        for object in self.objects.iter().filter(|object| predicate(object)) {
             result.extend(object.intersect(ray));
         }
         intersections(result)
     }
 
-    fn shade_hit(&self, comps: &Computations) -> Color {
-        self.lights
+    fn shade_hit(&self, comps: &Computations, sampler: &mut dyn Sampler) -> Color {
+        let material = comps.object.material();
+        let color =
+            material
+                .pattern
+                .pattern_at_shape_time(comps.object, &comps.over_point, comps.time);
+        let accessibility = match &self.ambient_occlusion {
+            Some(ao) => ao.accessibility(self, comps, sampler),
+            None => 1.,
+        };
+        let lit: Color = self
+            .lights
             .iter()
             .map(|light| {
-                let shadowed = self.is_shadowed(&comps.over_point, light);
-                comps.object.material().lighting(
-                    comps.object,
+                let transmittance = self.shadow_transmittance(comps, light);
+                material.lighting_of(
+                    &color,
                     light,
                     &comps.over_point,
                     &comps.eyev,
                     &comps.normalv,
-                    shadowed,
+                    &transmittance,
+                    accessibility,
                 )
             })
-            .sum()
+            .sum();
+        material.emissive + lit + self.background_light(comps, sampler)
+    }
+
+    fn background_light(&self, comps: &Computations, sampler: &mut dyn Sampler) -> Color {
+        if !self.background_lighting {
+            return Color::BLACK;
+        }
+        let material = comps.object.material();
+        let color =
+            material
+                .pattern
+                .pattern_at_shape_time(comps.object, &comps.over_point, comps.time);
+        if self.environment_samples == 0 {
+            return color * material.diffuse * self.background_at(&comps.normalv);
+        }
+
+        let mut total = Color::BLACK;
+        for _ in 0..self.environment_samples {
+            let Some(sample) = self.background.sample(sampler.next_2d()) else {
+                continue;
+            };
+            let reflected =
+                material.reflectance(&color, &sample.direction, &comps.eyev, &comps.normalv);
+            if reflected == Color::BLACK || sample.pdf <= 0. {
+                continue;
+            }
+            let shadow_ray = Ray::new(comps.over_point, sample.direction).with_time(comps.time);
+            if comps.object.receives_shadow() && self.is_occluded(&shadow_ray, Scalar::INFINITY) {
+                continue;
+            }
+            total += reflected * sample.radiance / (PI * sample.pdf);
+        }
+        total / self.environment_samples as Scalar
+    }
+
+    pub fn background_at(&self, direction: &Vector) -> Color {
+        self.background.color_at(direction)
     }
 
     pub fn color_at(&self, ray: &Ray) -> Color {
-        let intersections = self.intersect(ray);
+        self.color_at_sampled(ray, &mut RandomSampler::new(0))
+    }
+
+    pub fn color_at_sampled(&self, ray: &Ray, sampler: &mut dyn Sampler) -> Color {
+        let intersections = self.intersect_visible(ray);
         let hit = hit(&intersections);
         match hit {
-            None => Color::BLACK,
+            None => self.background_at(&ray.direction),
             Some(h) => {
                 let comps = Computations::prepare(h, ray);
-                self.shade_hit(&comps)
+                self.shade_hit(&comps, sampler)
             }
         }
     }
 
-    fn is_shadowed(&self, point: &Point, light: &PointLight) -> bool {
+    pub fn is_occluded(&self, ray: &Ray, max_distance: Scalar) -> bool {
+        self.intersect_matching(ray, Shape::casts_shadow)
+            .iter()
+            .any(|i| i.t > 0. && i.t < max_distance)
+    }
+
+    pub fn shadow_transmittance(&self, comps: &Computations, light: &PointLight) -> Color {
+        if comps.object.receives_shadow() {
+            self.light_transmittance(&comps.over_point, light, comps.time)
+        } else {
+            Color::WHITE
+        }
+    }
+
+    fn light_transmittance(&self, point: &Point, light: &PointLight, time: Scalar) -> Color {
         let v = light.position - *point;
         let distance = v.magnitude();
         let direction = v.normalize();
 
-        let r = Ray::new(*point, direction);
-        let intersections = self.intersect(&r);
-        match hit(&intersections) {
-            Some(h) => h.t < distance,
-            None => false,
+        let r = Ray::new(*point, direction).with_time(time);
+        let intersections = self.intersect_matching(&r, Shape::casts_shadow);
+        let mut transmittance = Color::WHITE;
+        for i in intersections.iter().filter(|i| i.t > 0. && i.t < distance) {
+            let material = i.object.material();
+            if material.transparency <= 0. {
+                return Color::BLACK;
+            }
+            let color = material
+                .pattern
+                .pattern_at_shape_time(i.object, &r.position(i.t), time);
+            transmittance = transmittance * color * material.transparency;
         }
+        transmittance
     }
 }
 
@@ -101,7 +299,13 @@ impl Default for World {
 mod tests {
 
     use super::*;
-    use crate::{intersections::Computations, rays::Ray};
+    use crate::{
+        backgrounds::{EnvironmentMap, VerticalGradient},
+        canvas::Canvas,
+        intersections::Computations,
+        patterns::Pattern,
+        rays::Ray,
+    };
     use approx::assert_abs_diff_eq;
 
     #[test]
@@ -134,7 +338,7 @@ mod tests {
         let i = Intersection::new(4., shape);
 
         let comps = Computations::prepare(&i, &r);
-        let c = w.shade_hit(&comps);
+        let c = w.shade_hit(&comps, &mut RandomSampler::new(0));
 
         assert_abs_diff_eq!(c, Tuple::color(0.38066, 0.47583, 0.2855), epsilon = 0.00001);
     }
@@ -148,7 +352,7 @@ mod tests {
         let i = Intersection::new(0.5, shape);
 
         let comps = Computations::prepare(&i, &r);
-        let c = w.shade_hit(&comps);
+        let c = w.shade_hit(&comps, &mut RandomSampler::new(0));
 
         assert_abs_diff_eq!(
             c,
@@ -199,7 +403,7 @@ mod tests {
         let w = World::default();
         let p = Tuple::point(0., 10., 0.);
 
-        assert!(!w.is_shadowed(&p, &w.lights[0]));
+        assert_eq!(w.light_transmittance(&p, &w.lights[0], 0.), Color::WHITE);
     }
 
     #[test]
@@ -207,7 +411,7 @@ mod tests {
         let w = World::default();
         let p = Tuple::point(10., -10., 10.);
 
-        assert!(w.is_shadowed(&p, &w.lights[0]));
+        assert_eq!(w.light_transmittance(&p, &w.lights[0], 0.), Color::BLACK);
     }
 
     #[test]
@@ -215,7 +419,7 @@ mod tests {
         let w = World::default();
         let p = Tuple::point(-20., 20., -20.);
 
-        assert!(!w.is_shadowed(&p, &w.lights[0]));
+        assert_eq!(w.light_transmittance(&p, &w.lights[0], 0.), Color::WHITE);
     }
 
     #[test]
@@ -223,7 +427,7 @@ mod tests {
         let w = World::default();
         let p = Tuple::point(-2., 2., -2.);
 
-        assert!(!w.is_shadowed(&p, &w.lights[0]));
+        assert_eq!(w.light_transmittance(&p, &w.lights[0], 0.), Color::WHITE);
     }
 
     #[test]
@@ -237,8 +441,294 @@ mod tests {
         let i = Intersection::new(4., &w.objects[1]);
 
         let comps = Computations::prepare(&i, &r);
-        let c = w.shade_hit(&comps);
+        let c = w.shade_hit(&comps, &mut RandomSampler::new(0));
 
         assert_eq!(c, Tuple::color(0.1, 0.1, 0.1));
     }
+
+    #[test]
+    fn an_emissive_surface_glows_without_lights() {
+        let mut s = Shape::sphere();
+        s.material_mut().emissive = Tuple::color(0.5, 0.25, 1.);
+        let mut w = World::new();
+        w.objects.push(s);
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
+
+        let c = w.color_at(&r);
+
+        assert_eq!(c, Tuple::color(0.5, 0.25, 1.));
+    }
+
+    #[test]
+    fn emission_is_added_once_and_ignores_shadows() {
+        let light = PointLight::new(Tuple::point(0., 0., -10.), Tuple::color(1., 1., 1.));
+        let s1 = Shape::sphere();
+        let mut s2 = Shape::sphere();
+        s2.set_transform(Transformation::translation(0., 0., 10.));
+        s2.material_mut().emissive = Tuple::color(0.2, 0.2, 0.2);
+        let mut w = World::with_objects_and_light(vec![s1, s2], light);
+        w.lights.push(PointLight::new(
+            Tuple::point(0., 0., -20.),
+            Tuple::color(1., 1., 1.),
+        ));
+        let r = Ray::new(Tuple::point(0., 0., 5.), Tuple::vector(0., 0., 1.));
+        let i = Intersection::new(4., &w.objects[1]);
+
+        let comps = Computations::prepare(&i, &r);
+        let c = w.shade_hit(&comps, &mut RandomSampler::new(0));
+
+        assert_abs_diff_eq!(c, Tuple::color(0.4, 0.4, 0.4), epsilon = 0.00001);
+    }
+
+    #[test]
+    fn an_invisible_object_is_not_seen_by_camera_rays() {
+        let mut w = World::default();
+        w.objects[0].set_visible(false);
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
+        let inner = &w.objects[1];
+        let i = Intersection::new(4.5, inner);
+
+        let c = w.color_at(&r);
+
+        assert_eq!(
+            c,
+            w.shade_hit(&Computations::prepare(&i, &r), &mut RandomSampler::new(0))
+        );
+    }
+
+    #[test]
+    fn an_invisible_object_still_casts_shadows() {
+        let mut w = World::default();
+        w.objects[0].set_visible(false);
+        let p = Tuple::point(10., -10., 10.);
+
+        assert_eq!(w.light_transmittance(&p, &w.lights[0], 0.), Color::BLACK);
+    }
+
+    #[test]
+    fn an_object_that_does_not_cast_shadows_lets_the_light_through() {
+        let mut w = World::default();
+        w.objects[0].set_casts_shadow(false);
+        w.objects[1].set_casts_shadow(false);
+        let p = Tuple::point(10., -10., 10.);
+
+        assert_eq!(w.light_transmittance(&p, &w.lights[0], 0.), Color::WHITE);
+    }
+
+    #[test]
+    fn an_object_that_does_not_receive_shadows_is_fully_lit() {
+        let light = PointLight::new(Tuple::point(0., 0., -10.), Tuple::color(1., 1., 1.));
+        let s1 = Shape::sphere();
+        let mut s2 = Shape::sphere();
+        s2.set_transform(Transformation::translation(0., 0., 10.));
+        s2.set_receives_shadow(false);
+        let w = World::with_objects_and_light(vec![s1, s2], light);
+        let r = Ray::new(Tuple::point(0., 0., 5.), Tuple::vector(0., 0., 1.));
+        let i = Intersection::new(4., &w.objects[1]);
+
+        let comps = Computations::prepare(&i, &r);
+        let c = w.shade_hit(&comps, &mut RandomSampler::new(0));
+
+        assert_abs_diff_eq!(c, Tuple::color(1.9, 1.9, 1.9), epsilon = 0.00001);
+    }
+
+    #[test]
+    fn light_passes_through_a_transparent_object() {
+        let mut w = World::default();
+        for object in &mut w.objects {
+            object.material_mut().set_color(Tuple::color(1., 1., 1.));
+            object.material_mut().transparency = 0.5;
+        }
+        let p = Tuple::point(10., -10., 10.);
+
+        let transmittance = w.light_transmittance(&p, &w.lights[0], 0.);
+
+        assert_abs_diff_eq!(transmittance, Tuple::color(0.0625, 0.0625, 0.0625));
+    }
+
+    #[test]
+    fn a_tinted_transparent_object_casts_a_colored_shadow() {
+        let light = PointLight::new(Tuple::point(0., 0., -10.), Tuple::color(1., 1., 1.));
+        let mut s = Shape::sphere();
+        s.material_mut().set_color(Tuple::color(1., 0.5, 0.));
+        s.material_mut().transparency = 1.;
+        let w = World::with_objects_and_light(vec![s], light);
+        let p = Tuple::point(0., 0., 10.);
+
+        let transmittance = w.light_transmittance(&p, &w.lights[0], 0.);
+
+        assert_abs_diff_eq!(transmittance, Tuple::color(1., 0.25, 0.));
+    }
+
+    #[test]
+    fn an_opaque_object_behind_a_transparent_one_blocks_the_light() {
+        let mut w = World::default();
+        w.objects[0].material_mut().transparency = 1.;
+        let p = Tuple::point(10., -10., 10.);
+
+        assert_eq!(w.light_transmittance(&p, &w.lights[0], 0.), Color::BLACK);
+    }
+
+    #[test]
+    fn a_world_has_no_ambient_occlusion_by_default() {
+        let w = World::default();
+
+        assert!(w.ambient_occlusion.is_none());
+    }
+
+    #[test]
+    fn ambient_occlusion_darkens_the_ambient_term_of_enclosed_surfaces() {
+        let mut w = World::default();
+        let inner = &mut w.objects[1];
+        inner.material_mut().ambient = 1.;
+        inner.material_mut().diffuse = 0.;
+        inner.material_mut().specular = 0.;
+        let r = Ray::new(Tuple::point(0., 0., 0.75), Tuple::vector(0., 0., -1.));
+        let unoccluded = w.color_at(&r);
+
+        w.set_ambient_occlusion(Some(AmbientOcclusion::new(16, 0.1)));
+        let nearby_only = w.color_at(&r);
+        w.set_ambient_occlusion(Some(AmbientOcclusion::new(16, 10.)));
+        let enclosed = w.color_at(&r);
+
+        assert_eq!(nearby_only, unoccluded);
+        assert_abs_diff_eq!(enclosed, Color::BLACK);
+    }
+
+    #[test]
+    fn a_ray_is_occluded_by_objects_within_the_distance() {
+        let w = World::default();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
+
+        assert!(w.is_occluded(&r, 4.5));
+        assert!(!w.is_occluded(&r, 3.5));
+    }
+
+    #[test]
+    fn finding_the_index_of_an_object() {
+        let w = World::default();
+        let stranger = Shape::sphere();
+
+        assert_eq!(w.object_index(&w.objects[1]), Some(1));
+        assert_eq!(w.object_index(&stranger), None);
+    }
+
+    #[test]
+    fn objects_sharing_a_material_share_a_material_id() {
+        let mut w = World::default();
+        let mut s3 = Shape::plane();
+        *s3.material_mut() = w.objects[0].material().clone();
+        w.objects.push(s3);
+
+        assert_eq!(w.material_ids(), vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn equal_materials_built_separately_share_a_material_id() {
+        let mut w = World::default();
+        let mut s3 = Shape::plane();
+        s3.material_mut().pattern = Pattern::stripe(Color::WHITE, Color::BLACK);
+        let mut s4 = Shape::sphere();
+        s4.material_mut().pattern = Pattern::stripe(Color::WHITE, Color::BLACK);
+        w.objects.push(Shape::plane());
+        w.objects.push(s3);
+        w.objects.push(s4);
+
+        assert_eq!(w.material_ids(), vec![0, 1, 1, 3, 3]);
+    }
+
+    #[test]
+    fn the_background_is_black_by_default() {
+        let w = World::default();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 1., 0.));
+
+        assert_eq!(w.color_at(&r), Color::BLACK);
+        assert!(!w.background_lighting());
+    }
+
+    #[test]
+    fn the_color_when_a_ray_misses_is_the_background() {
+        let mut w = World::default();
+        w.set_background(Box::new(VerticalGradient::new(
+            Color::BLACK,
+            Tuple::color(0.2, 0.4, 0.8),
+        )));
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 1., 0.));
+
+        assert_eq!(w.color_at(&r), Tuple::color(0.2, 0.4, 0.8));
+    }
+
+    #[test]
+    fn the_background_only_lights_surfaces_when_enabled() {
+        let mut w = World::default();
+        w.set_background(Box::new(Constant::new(Tuple::color(0.5, 0.5, 0.5))));
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::vector(0., 0., 1.));
+        let unlit = w.color_at(&r);
+
+        w.set_background_lighting(true);
+        let lit = w.color_at(&r);
+
+        assert_abs_diff_eq!(lit - unlit, Tuple::color(0.28, 0.35, 0.21));
+    }
+
+    fn sky_lit_floor(sky: Box<dyn Background>) -> World {
+        let mut floor = Shape::plane();
+        floor.material_mut().specular = 0.;
+        floor.material_mut().ambient = 0.;
+        let mut w = World::with_objects_and_light(
+            vec![floor],
+            PointLight::new(Tuple::point(0., 10., 0.), Color::BLACK),
+        );
+        w.set_background(sky);
+        w.set_background_lighting(true);
+        w
+    }
+
+    #[test]
+    fn sampling_a_uniform_sky() {
+        let mut w = sky_lit_floor(Box::new(Constant::new(Color::WHITE)));
+        w.set_environment_samples(4096);
+        let r = Ray::new(Tuple::point(0., 1., 0.), Tuple::vector(0., -1., 0.));
+
+        let c = w.color_at(&r);
+
+        assert_abs_diff_eq!(c, Tuple::color(0.9, 0.9, 0.9), epsilon = 0.03);
+    }
+
+    #[test]
+    fn sampling_an_environment_map_by_luminance() {
+        let mut image = Canvas::new(16, 8);
+        image.write_pixel(3, 1, Tuple::color(200., 100., 50.));
+        let mut w = sky_lit_floor(Box::new(EnvironmentMap::new(image)));
+        w.set_environment_samples(16);
+        let r = Ray::new(Tuple::point(0., 1., 0.), Tuple::vector(0., -1., 0.));
+
+        let c = w.color_at(&r);
+
+        assert!(c.red() > 0.);
+        assert_abs_diff_eq!(c.green(), c.red() / 2., epsilon = 1e-9);
+        assert_abs_diff_eq!(c.blue(), c.red() / 4., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn objects_block_light_from_the_environment() {
+        let mut w = sky_lit_floor(Box::new(Constant::new(Color::WHITE)));
+        w.set_environment_samples(256);
+        let r = Ray::new(Tuple::point(0., 1., 0.), Tuple::vector(0., -1., 0.));
+        let open = w.color_at(&r);
+
+        let mut ball = Shape::sphere();
+        ball.set_transform(
+            Transformation::translation(0., 2., 0.) * Transformation::scaling(1.5, 1.5, 1.5),
+        );
+        ball.set_visible(false);
+        w.objects.push(ball);
+        let shadowed = w.color_at(&r);
+
+        assert!(shadowed.red() < open.red() - 0.2);
+        assert!(shadowed.red() > 0.);
+
+        w.objects[0].set_receives_shadow(false);
+        assert_eq!(w.color_at(&r), open);
+    }
 }
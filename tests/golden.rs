@@ -0,0 +1,113 @@
+use rust_tracer::{
+    canvas::Canvas,
+    tuples::{Scalar, Tuple},
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+#[allow(dead_code)]
+#[path = "../examples/plane.rs"]
+mod plane;
+#[allow(dead_code)]
+#[path = "../examples/scene.rs"]
+mod scene;
+#[allow(dead_code)]
+#[path = "../examples/shadows.rs"]
+mod shadows;
+
+/// Largest difference allowed in any channel of any pixel.
+const PIXEL_TOLERANCE: Scalar = 2. / 255.;
+/// Smallest peak signal-to-noise ratio, in decibels, over the whole image.
+const MIN_PSNR: Scalar = 40.;
+
+fn reference_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"))
+}
+
+fn output_path(name: &str) -> PathBuf {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/golden");
+    fs::create_dir_all(&directory).unwrap();
+    directory.join(format!("{name}.png"))
+}
+
+/// Clamps a render the way saving it as a PNG does, leaving only quantization
+/// differences against its reference.
+fn clamped(canvas: &Canvas) -> Canvas {
+    let mut result = Canvas::new(canvas.width(), canvas.height());
+    for y in 0..canvas.height() {
+        for x in 0..canvas.width() {
+            let color = canvas.pixel_at(x, y);
+            result.write_pixel(
+                x,
+                y,
+                Tuple::color(
+                    color.red().clamp(0., 1.),
+                    color.green().clamp(0., 1.),
+                    color.blue().clamp(0., 1.),
+                ),
+            );
+        }
+    }
+    result
+}
+
+/// Compares a render with its stored reference. Setting `UPDATE_GOLDEN` replaces the
+/// reference instead. On failure the render and a heat map of the differences are
+/// written to `target/golden`.
+fn assert_matches_golden(name: &str, actual: &Canvas) {
+    let reference_path = reference_path(name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(reference_path.parent().unwrap()).unwrap();
+        actual.save_to_file(&reference_path).unwrap();
+        return;
+    }
+    let expected = Canvas::load_from_file(&reference_path).unwrap_or_else(|error| {
+        panic!(
+            "Cannot load {}: {error}. Run with UPDATE_GOLDEN=1 to create it.",
+            reference_path.display()
+        )
+    });
+    assert_eq!(
+        (actual.width(), actual.height()),
+        (expected.width(), expected.height()),
+        "{name}: size differs from the reference"
+    );
+
+    let clamped = clamped(actual);
+    let worst = clamped.max_difference(&expected);
+    let psnr = clamped.psnr(&expected);
+
+    if worst > PIXEL_TOLERANCE || psnr < MIN_PSNR {
+        actual.save_to_file(&output_path(name)).unwrap();
+        clamped
+            .heat_map(&expected, PIXEL_TOLERANCE * 4.)
+            .save_to_file(&output_path(&format!("{name}-diff")))
+            .unwrap();
+        panic!(
+            "{name}: largest difference {worst:.4}, PSNR {psnr:.2} dB, SSIM {:.4}; see target/golden/{name}-diff.png",
+            clamped.ssim(&expected)
+        );
+    }
+}
+
+#[test]
+fn plane_example_matches_its_reference() {
+    assert_matches_golden("plane", &plane::camera(64, 48).render(&plane::world()));
+}
+
+#[test]
+fn scene_example_matches_its_reference() {
+    assert_matches_golden("scene", &scene::camera(64, 32).render(&scene::world()));
+}
+
+#[test]
+fn shadows_example_matches_its_reference() {
+    assert_matches_golden(
+        "shadows",
+        &shadows::camera(64, 32).render(&shadows::world()),
+    );
+}